@@ -0,0 +1,109 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use error_trees::*;
+
+/// Builds a tree with `width` labeled subtrees of `width` labeled errors each.
+fn tree(width: usize) -> ErrorTree<usize, usize> {
+    ErrorTree::Vec(
+        (0..width)
+            .map(|i| {
+                ErrorTree::Vec(
+                    (0..width)
+                        .map(|j| ErrorTree::leaf(i * width + j).with_label(j))
+                        .collect(),
+                )
+                .with_label(i)
+            })
+            .collect(),
+    )
+}
+
+fn flatten_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flatten_tree");
+    group.bench_function("leaf", |b| {
+        b.iter(|| ErrorTree::<usize, usize>::leaf(black_box(0)).flatten_tree())
+    });
+    group.bench_function("single_edge", |b| {
+        b.iter(|| {
+            ErrorTree::<usize, usize>::leaf(black_box(0))
+                .with_label(0)
+                .flatten_tree()
+        })
+    });
+    for width in [4, 16, 64] {
+        group.bench_with_input(BenchmarkId::from_parameter(width), &width, |b, &width| {
+            b.iter_batched(
+                || tree(width),
+                ErrorTree::flatten_tree,
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn flatten_each(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flatten_each");
+    for width in [4, 16, 64] {
+        group.bench_with_input(
+            BenchmarkId::new("flatten_tree", width),
+            &width,
+            |b, &width| {
+                b.iter_batched(
+                    || tree(width),
+                    ErrorTree::flatten_tree,
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("flatten_each", width),
+            &width,
+            |b, &width| {
+                b.iter_batched(
+                    || tree(width),
+                    |tree| {
+                        let mut depths = 0;
+                        tree.flatten_each(|path, error| depths += path.len() + black_box(error));
+                        depths
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn with_label(c: &mut Criterion) {
+    let mut group = c.benchmark_group("with_label");
+    for width in [4, 16, 64] {
+        group.bench_with_input(BenchmarkId::from_parameter(width), &width, |b, &width| {
+            b.iter_batched(
+                || tree(width),
+                |tree| tree.with_label(black_box(0)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn count_leaves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("count_leaves");
+    for width in [4, 16, 64] {
+        let tree = tree(width);
+        group.bench_with_input(BenchmarkId::from_parameter(width), &tree, |b, tree| {
+            b.iter(|| tree.count_leaves())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    flatten_tree,
+    flatten_each,
+    with_label,
+    count_leaves
+);
+criterion_main!(benches);
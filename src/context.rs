@@ -0,0 +1,82 @@
+//! `anyhow` integration, available with the `anyhow` feature.
+use std::fmt::Display;
+
+use crate::ErrorTree;
+
+/// Debug formats the error with its `Display`, as `anyhow::Error::msg` needs both.
+struct DisplayError<E>(E);
+
+impl<E: Display> Display for DisplayError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E: Display> std::fmt::Debug for DisplayError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Display,
+    E: Display + Send + Sync + 'static,
+{
+    /**
+    Converts each error into an `anyhow::Error`, with the labels above it as context,
+    the outermost label being the last context.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    let errors = tree.into_anyhow_vec();
+
+    assert_eq!(format!("{:#}", errors[0]), "parent_label: label: error");
+    ```
+    */
+    pub fn into_anyhow_vec(self) -> Vec<anyhow::Error> {
+        let mut errors = Vec::new();
+        self.flatten_each(|path, error| {
+            let error = anyhow::Error::msg(DisplayError(error));
+            errors.push(
+                path.iter()
+                    .rev()
+                    .fold(error, |error, label| error.context(label.to_string())),
+            );
+        });
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use itertools::Itertools;
+
+    use crate::IntoErrorTree;
+
+    #[test]
+    fn into_anyhow_vec_chains_the_labels_of_each_error() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let messages = tree
+            .into_anyhow_vec()
+            .into_iter()
+            .map(|error| format!("{:#}", error))
+            .collect_vec();
+
+        assert_eq!(
+            messages,
+            vec!["parent_label: label1: error1", "parent_label: error2"]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! `ariadne` integration, available with the `ariadne` feature.
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::ops::Range;
+
+use ariadne::{Label, Report, ReportKind};
+
+use crate::ErrorTree;
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Clone + Into<Range<usize>>,
+    E: Display,
+{
+    /**
+    Converts the tree into an `ariadne::Report` on the source `src_id`,
+    with one `ariadne::Label` per error.
+
+    Each error points to the range of its innermost label, and the report to the range of the first error.
+    The errors without labels point to the start of the source.
+
+    # Panics
+
+    Panics if a range ends before it starts.
+
+    ```rust
+    # use std::ops::Range;
+    # use error_trees::*;
+    let src = "port = \"eighty\"";
+    let tree: ErrorTree<Range<usize>, &'static str> = ErrorTree::leaf("expected a number").with_label(7..15);
+
+    let report = tree.into_ariadne("config.toml");
+
+    let mut output = Vec::new();
+    report
+        .write(("config.toml", ariadne::Source::from(src)), &mut output)
+        .unwrap();
+    assert!(String::from_utf8(output).unwrap().contains("expected a number"));
+    ```
+    */
+    pub fn into_ariadne<Id>(self, src_id: Id) -> Report<'static, (Id, Range<usize>)>
+    where
+        Id: Debug + Hash + Eq + Clone,
+    {
+        let errors = self
+            .flatten_tree()
+            .into_iter()
+            .map(|flat_error| {
+                let range = flat_error.path.into_iter().next().map_or(0..0, Into::into);
+                (range, flat_error.error)
+            })
+            .collect::<Vec<_>>();
+        let span = errors.first().map_or(0..0, |(range, _)| range.clone());
+        let message = match errors.len() {
+            1 => "1 error".to_string(),
+            count => format!("{} errors", count),
+        };
+        Report::build(ReportKind::Error, (src_id.clone(), span))
+            .with_message(message)
+            .with_labels(
+                errors
+                    .into_iter()
+                    .map(|(range, error)| Label::new((src_id.clone(), range)).with_message(error)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ariadne::Source;
+
+    use crate::IntoErrorTree;
+
+    #[test]
+    fn into_ariadne_labels_every_error() {
+        let src = "name = 1\nport = \"eighty\"\n";
+        let tree: ErrorTree<Range<usize>, &'static str> = vec![
+            ErrorTree::leaf("expected a string").with_label(7..8),
+            ErrorTree::leaf("expected a number").with_label(16..24),
+        ]
+        .into();
+
+        let report = tree.into_ariadne("config.toml");
+
+        let mut output = Vec::new();
+        report
+            .write(("config.toml", Source::from(src)), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("2 errors"));
+        assert!(output.contains("expected a string"));
+        assert!(output.contains("expected a number"));
+    }
+}
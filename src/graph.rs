@@ -0,0 +1,90 @@
+//! `petgraph` integration, available with the `petgraph` feature.
+use petgraph::graph::{Graph, NodeIndex};
+
+use crate::ErrorTree;
+
+/// The kind of a node of the graph built by `ErrorTree::to_petgraph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind<L, E> {
+    /// An `Edge` of the tree, with its label.
+    Label(L),
+    /// A `Leaf` of the tree, with its error.
+    Error(E),
+    /// A `Vec` of the tree.
+    Group,
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Clone,
+    E: Clone,
+{
+    /**
+    Converts the tree into a `petgraph::Graph`.
+
+    Every node of the tree becomes a node of the graph, with an edge from each
+    node to each of its subtrees. The root is the node with index `0`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    let graph = tree.to_petgraph();
+
+    assert_eq!(graph.node_count(), 2);
+    assert_eq!(graph.edge_count(), 1);
+    ```
+    */
+    pub fn to_petgraph(&self) -> Graph<NodeKind<L, E>, ()> {
+        let mut graph = Graph::new();
+        let mut stack: Vec<(Option<NodeIndex>, &ErrorTree<L, E>)> = vec![(None, self)];
+        while let Some((parent, tree)) = stack.pop() {
+            let node = match tree {
+                ErrorTree::Leaf(error) => graph.add_node(NodeKind::Error(error.clone())),
+                ErrorTree::Edge(label, _) => graph.add_node(NodeKind::Label(label.clone())),
+                ErrorTree::Vec(_) => graph.add_node(NodeKind::Group),
+            };
+            if let Some(parent) = parent {
+                graph.add_edge(parent, node, ());
+            }
+            match tree {
+                ErrorTree::Leaf(_) => {}
+                ErrorTree::Edge(_, subtree) => stack.push((Some(node), subtree)),
+                ErrorTree::Vec(subtrees) => {
+                    stack.extend(subtrees.iter().rev().map(|subtree| (Some(node), subtree)))
+                }
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IntoErrorTree;
+
+    #[test]
+    fn to_petgraph_has_a_node_per_tree_node() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let graph = tree.to_petgraph();
+
+        let node_count = tree.assign_ids().len();
+        assert_eq!(graph.node_count(), node_count);
+        assert_eq!(graph.edge_count(), node_count - 1);
+        assert_eq!(graph[NodeIndex::new(0)], NodeKind::Label("parent_label"));
+        assert_eq!(
+            graph
+                .neighbors(NodeIndex::new(0))
+                .map(|node| &graph[node])
+                .collect::<Vec<_>>(),
+            vec![&NodeKind::Group]
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Async support, available with the `async` feature.
+use std::future::Future;
+
+use futures::future::join_all;
+
+use crate::{CollectTree, ErrorTree};
+
+/**
+Awaits all the `futures` concurrently, collecting the successes in a `Vec`,
+or all the errors in an `ErrorTree`.
+
+This is the async version of `collect_tree`: it doesn't stop at the first error.
+
+```rust
+# use error_trees::*;
+#[derive(Debug)]
+struct Error(String);
+
+async fn fetch(id: u32) -> Result<u32, ErrorTree<String, Error>> {
+    if id % 2 == 0 {
+        Ok(id)
+    } else {
+        Err(ErrorTree::leaf(Error("odd".into())).with_label(format!("fetch {}", id)))
+    }
+}
+
+# futures::executor::block_on(async {
+let result = collect_tree_async((0..4).map(fetch)).await;
+
+assert_eq!(result.unwrap_err().flatten_tree().len(), 2);
+# });
+```
+*/
+pub async fn collect_tree_async<T, L, E, Fut, I>(futures: I) -> Result<Vec<T>, ErrorTree<L, E>>
+where
+    I: IntoIterator<Item = Fut>,
+    Fut: Future<Output = Result<T, ErrorTree<L, E>>>,
+{
+    join_all(futures).await.into_iter().collect_tree()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::{FlattenResultErrors, IntoErrorTree};
+
+    async fn check(value: u32) -> Result<u32, ErrorTree<u32, &'static str>> {
+        if value > 1 {
+            Err(ErrorTree::leaf("too big").with_label(value))
+        } else {
+            Ok(value)
+        }
+    }
+
+    #[test]
+    fn collect_tree_async_gathers_all_errors() {
+        let result = block_on(collect_tree_async((0..4).map(check)));
+
+        let flat_errors = result.flatten_results().unwrap_err();
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect::<Vec<_>>(),
+            vec![(vec![2], "too big"), (vec![3], "too big")]
+        );
+    }
+
+    #[test]
+    fn collect_tree_async_keeps_successes() {
+        let result = block_on(collect_tree_async((0..2).map(check)));
+
+        assert_eq!(result.unwrap(), vec![0, 1]);
+    }
+}
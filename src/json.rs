@@ -0,0 +1,105 @@
+//! Reading error trees from JSON values, available with the `serde` feature.
+use itertools::Itertools;
+use serde_json::{Map, Value};
+
+use crate::{ErrorTree, ParseError};
+
+impl ErrorTree<String, String> {
+    /**
+    Builds a tree from a JSON value, accepting the format written by `to_json`:
+
+    - `{"error": "..."}` becomes a `Leaf`.
+    - `{"label": "...", "children": [...]}` becomes an `Edge`, over its only child or over a `Vec` of its children.
+    - `{"children": [...]}` becomes a `Vec`.
+
+    For looser payloads, a string is also read as a `Leaf` and an array as a `Vec`.
+
+    ```rust
+    # use error_trees::*;
+    let value = serde_json::json!({"label": "label", "children": [{"error": "error"}]});
+
+    let tree = ErrorTree::from_json_value(value).unwrap();
+
+    assert_eq!(tree.flatten_tree()[0].path, vec!["label".to_string()]);
+    ```
+    */
+    pub fn from_json_value(value: Value) -> Result<Self, ParseError> {
+        match value {
+            Value::String(error) => Ok(ErrorTree::Leaf(error)),
+            Value::Array(values) => Ok(ErrorTree::Vec(from_json_values(values)?)),
+            Value::Object(object) => from_json_object(object),
+            _ => Err(ParseError::InvalidJson),
+        }
+    }
+}
+
+fn from_json_values(values: Vec<Value>) -> Result<Vec<ErrorTree<String, String>>, ParseError> {
+    values
+        .into_iter()
+        .map(ErrorTree::from_json_value)
+        .try_collect()
+}
+
+fn from_json_object(
+    mut object: Map<String, Value>,
+) -> Result<ErrorTree<String, String>, ParseError> {
+    let error = object.remove("error");
+    let label = object.remove("label");
+    let children = object.remove("children");
+    match (error, label, children) {
+        (Some(Value::String(error)), None, None) => Ok(ErrorTree::Leaf(error)),
+        (None, Some(Value::String(label)), Some(Value::Array(values))) => {
+            let mut children = from_json_values(values)?;
+            let tree = if children.len() == 1 {
+                children.remove(0)
+            } else {
+                ErrorTree::Vec(children)
+            };
+            Ok(ErrorTree::Edge(label, Box::new(tree)))
+        }
+        (None, None, Some(Value::Array(values))) => Ok(ErrorTree::Vec(from_json_values(values)?)),
+        _ => Err(ParseError::InvalidJson),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::IntoErrorTree;
+
+    #[test]
+    fn from_json_value_reads_back_to_json() {
+        let tree: ErrorTree<String, String> = vec![
+            ErrorTree::leaf("error1".to_string()).with_label("label1".to_string()),
+            ErrorTree::Vec(vec![ErrorTree::leaf("error \"2\"".to_string())])
+                .with_label("label2".to_string()),
+            ErrorTree::Vec(vec![]).with_label("label3".to_string()),
+            ErrorTree::leaf("error3".to_string()),
+        ]
+        .into();
+        let json = tree.with_label("parent_label".to_string()).to_json();
+
+        let value = serde_json::from_str(&json).unwrap();
+        let parsed = ErrorTree::from_json_value(value).unwrap();
+
+        assert_eq!(parsed.to_json(), json);
+    }
+
+    #[test]
+    fn from_json_value_rejects_other_shapes() {
+        let values = [
+            serde_json::json!(1),
+            serde_json::json!({"error": 1}),
+            serde_json::json!({"label": "label"}),
+            serde_json::json!({"error": "error", "children": []}),
+        ];
+
+        for value in values {
+            assert_eq!(
+                ErrorTree::from_json_value(value).unwrap_err(),
+                ParseError::InvalidJson
+            );
+        }
+    }
+}
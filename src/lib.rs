@@ -5,8 +5,42 @@
 //! it helps you store the errors that occur in a tree structure.
 //! It lets you label the errors, and flatten then into a list
 //! to present to the user.
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::panic::Location;
+
 use itertools::Itertools;
 
+#[cfg(feature = "anyhow")]
+mod context;
+#[cfg(feature = "ariadne")]
+mod diagnostic;
+#[cfg(feature = "petgraph")]
+mod graph;
+#[cfg(feature = "async")]
+mod join;
+#[cfg(feature = "serde")]
+mod json;
+mod report;
+#[cfg(feature = "proc-macro2")]
+mod span;
+#[cfg(feature = "proptest")]
+mod strategy;
+mod text;
+
+#[cfg(feature = "petgraph")]
+pub use graph::NodeKind;
+#[cfg(feature = "async")]
+pub use join::collect_tree_async;
+pub use report::Report;
+#[cfg(feature = "proc-macro2")]
+pub use span::SpannedLabel;
+#[cfg(feature = "proptest")]
+pub use strategy::error_tree_strategy;
+pub use text::ParseError;
+
 /// The error Tree structure.
 ///
 /// - `L` is the Label type.
@@ -21,6 +55,42 @@ pub enum ErrorTree<L, E> {
     Vec(Vec<ErrorTree<L, E>>),
 }
 
+/// The contents of an `ErrorTree` node, taken apart by `ErrorTree::into_node`.
+///
+/// `ErrorTree` implements `Drop`, so its variants can't be moved out of by a pattern.
+/// `Node` has the same variants, and can.
+#[derive(Debug)]
+pub enum Node<L, E> {
+    /// The error of a `Leaf`.
+    Leaf(E),
+    /// The label and subtree of an `Edge`.
+    Edge(L, Box<ErrorTree<L, E>>),
+    /// The subtrees of a `Vec`.
+    Vec(Vec<ErrorTree<L, E>>),
+}
+
+impl<L, E> From<Node<L, E>> for ErrorTree<L, E> {
+    fn from(node: Node<L, E>) -> Self {
+        match node {
+            Node::Leaf(error) => ErrorTree::Leaf(error),
+            Node::Edge(label, tree) => ErrorTree::Edge(label, tree),
+            Node::Vec(trees) => ErrorTree::Vec(trees),
+        }
+    }
+}
+
+/// Drops the subtrees iteratively, so dropping very deep trees doesn't overflow the stack.
+impl<L, E> Drop for ErrorTree<L, E> {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.take_subtrees(&mut stack);
+        while let Some(mut tree) = stack.pop() {
+            // Without its subtrees, `tree` is dropped without recursing.
+            tree.take_subtrees(&mut stack);
+        }
+    }
+}
+
 impl<L, E> ErrorTree<L, E> {
     /**
     Creates a `Leaf` tree from an `error`.
@@ -35,375 +105,6411 @@ impl<L, E> ErrorTree<L, E> {
     pub fn leaf(error: E) -> Self {
         Self::Leaf(error)
     }
-}
 
-/// The flattened error type
-#[derive(Debug)]
-pub struct FlatError<L, E> {
-    /// The path from the leaf to the root of the tree.
-    pub path: Vec<L>,
-    /// The error
-    pub error: E,
-}
+    /// Moves the subtrees that have their own subtrees to `stack`, leaving the tree shallow.
+    fn take_subtrees(&mut self, stack: &mut Vec<ErrorTree<L, E>>) {
+        match self {
+            ErrorTree::Leaf(_) => {}
+            ErrorTree::Edge(_, tree) => match &mut **tree {
+                ErrorTree::Leaf(_) => {}
+                ErrorTree::Edge(..) => {
+                    stack.push(std::mem::replace(tree, ErrorTree::Vec(Vec::new())))
+                }
+                ErrorTree::Vec(trees) => stack.append(trees),
+            },
+            ErrorTree::Vec(trees) => stack.append(trees),
+        }
+    }
 
-impl<L, E> ErrorTree<L, E>
-where
-    L: Clone,
-{
     /**
-    Flattens the error tree in a `Vec` of `FlatError`s.
+    Takes the node apart, to move its error, label or subtrees out.
 
     ```rust
-    # use itertools::*;
     # use error_trees::*;
-    #[derive(Debug)]
-    struct Error(String);
-
-    let error_1 = ErrorTree::leaf(Error("error1".into())).with_label("label1");
-    let error_2 = ErrorTree::leaf(Error("error2".into())).with_label("label2");
-
-    let errors = vec![error_1, error_2];
-
-    let tree: ErrorTree<&'static str, Error> = errors.into();
-    let tree = tree.with_label("parent_label");
-
-    let flat_errors = tree.flatten_tree();
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
 
-    assert!(
-        matches!(
-            &flat_errors[..],
-            [
-                FlatError {
-                    path: path1,
-                    error: Error(error1),
-                },
-                FlatError {
-                    path: path2,
-                    error: Error(error2),
-                },
-            ]
-            if path1 == &vec!["label1", "parent_label"]
-            && path2 == &vec!["label2", "parent_label"]
-            && error1 == "error1"
-            && error2 == "error2"
-        ),
-        "unexpected: {:#?}",
-        flat_errors
-    );
+    match tree.into_node() {
+        Node::Edge(label, tree) => {
+            assert_eq!(label, "label");
+            assert!(matches!(tree.into_node(), Node::Leaf("error")));
+        }
+        _ => unreachable!(),
+    }
     ```
     */
-    pub fn flatten_tree(self) -> Vec<FlatError<L, E>> {
-        match self {
-            ErrorTree::Leaf(error) => vec![FlatError {
-                path: Vec::new(),
-                error,
-            }],
-            ErrorTree::Edge(label, tree) => {
-                let mut flat_errors = tree.flatten_tree();
-                for flat in &mut flat_errors {
-                    flat.path.push(label.clone());
+    pub fn into_node(self) -> Node<L, E> {
+        let tree = std::mem::ManuallyDrop::new(self);
+        // SAFETY: each field is read once, and the tree is never dropped, so they are moved out.
+        unsafe {
+            match &*tree {
+                ErrorTree::Leaf(error) => Node::Leaf(std::ptr::read(error)),
+                ErrorTree::Edge(label, tree) => {
+                    Node::Edge(std::ptr::read(label), std::ptr::read(tree))
                 }
-                flat_errors
+                ErrorTree::Vec(trees) => Node::Vec(std::ptr::read(trees)),
             }
-            ErrorTree::Vec(errors) => errors
-                .into_iter()
-                .flat_map(|tree| tree.flatten_tree())
-                .collect_vec(),
         }
     }
-}
 
-/// Adds a label to the error tree.
-pub trait IntoErrorTree<L, E> {
     /**
-    Adds a `label` to an error tree.
+    Creates a labeled node that has its own `error` as well as the errors of its `children`.
+
+    The node is encoded as an `Edge` over a `Vec` whose first subtree is the node's own error,
+    so `flatten_tree` emits the node's error first, followed by the errors of the children.
+
     ```rust
     # use error_trees::*;
-    struct Error(String);
-    let leaf = ErrorTree::leaf(Error("a regular error".into()));
-    let labeled_leaf = leaf.with_label("the label");
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::labeled_with_children(
+        "config",
+        "config invalid",
+        vec![ErrorTree::leaf("missing field").with_label("port")],
+    );
+
+    let flat_errors = tree.flatten_tree();
+
+    assert_eq!(flat_errors[0].path, vec!["config"]);
+    assert_eq!(flat_errors[1].path, vec!["port", "config"]);
     ```
     */
-    fn with_label(self, label: L) -> ErrorTree<L, E>;
-}
-
-impl<L, E> IntoErrorTree<L, E> for E
-where
-    E: Into<ErrorTree<L, E>>,
-{
-    fn with_label(self, label: L) -> ErrorTree<L, E> {
-        ErrorTree::Edge(label, Box::new(self.into()))
+    pub fn labeled_with_children(label: L, error: E, children: Vec<ErrorTree<L, E>>) -> Self {
+        let mut trees = Vec::with_capacity(children.len() + 1);
+        trees.push(Self::Leaf(error));
+        trees.extend(children);
+        Self::Edge(label, Box::new(Self::Vec(trees)))
     }
-}
 
-impl<L, E> IntoErrorTree<L, E> for ErrorTree<L, E> {
-    fn with_label(self, label: L) -> ErrorTree<L, E> {
-        ErrorTree::Edge(label, Box::new(self))
-    }
-}
+    /**
+    Creates a labeled `Leaf` tree from an optional `error`.
 
-impl<L, E> From<Vec<ErrorTree<L, E>>> for ErrorTree<L, E> {
-    fn from(subtrees: Vec<ErrorTree<L, E>>) -> Self {
-        ErrorTree::Vec(subtrees)
-    }
-}
+    ```rust
+    # use error_trees::*;
+    let tree: Option<ErrorTree<_, &'static str>> = ErrorTree::from_option("label", Some("error"));
+    assert!(tree.is_some());
 
-impl<L, E> From<Vec<E>> for ErrorTree<L, E>
-where
-    E: IntoErrorTree<L, E>,
-    ErrorTree<L, E>: From<E>,
-{
-    fn from(errors: Vec<E>) -> Self {
-        ErrorTree::Vec(errors.into_iter().map(|x| x.into()).collect_vec())
+    let tree: Option<ErrorTree<_, &'static str>> = ErrorTree::from_option("label", None);
+    assert!(tree.is_none());
+    ```
+    */
+    pub fn from_option(label: L, error: Option<E>) -> Option<Self> {
+        error.map(|error| Self::leaf(error).with_label(label))
     }
-}
 
-/// Convenience trait to convert tuple of `(success: T, errors: Vec<E>)` to a `result : Result<T, ErrorTree<L, E>>`
-pub trait IntoResult<T, E> {
     /**
-    Turns `self` into a `Result`.
+    Assigns a sequential id to every node of the tree.
 
-    For tuples of `(success: T, errors: Vec<E>)`:
-    - It checks if `errors` is empty.
-        - If true, it will return `Ok(success)`.
-        - Otherwise it will return `Err(errors)`.
+    The ids are assigned in pre-order: the root gets the id `0`, and every node
+    is numbered before its subtrees, which are visited from the first to the last.
+    So the ids are the same for every call on the same tree.
 
     ```rust
-    # use itertools::*;
     # use error_trees::*;
-    struct Error(String);
-    impl<L> From<Error> for ErrorTree<L, Error> {
-        fn from(e: Error) -> Self {
-            Self::leaf(e)
-        }
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    let ids = tree.assign_ids();
+
+    assert!(matches!(&ids[..], [(0, ErrorTree::Edge("label", _)), (1, ErrorTree::Leaf("error"))]));
+    ```
+    */
+    pub fn assign_ids(&self) -> Vec<(NodeId, &ErrorTree<L, E>)> {
+        self.nodes().into_iter().enumerate().collect_vec()
     }
 
-    let result1: Result<(), _> = Err(Error("first".into())).label_error("one");
-    let result2: Result<(), _> = Err(Error("second".into())).label_error("two");
+    /**
+    Returns the subtree reached by following `indices` through the `Vec` nodes.
 
-    let final_result: Result<Vec<_>, ErrorTree<_, _>> = vec![result1, result2]
-        .into_iter()
-        .partition_result()
-        .into_result();
+    Each index selects a subtree of the next `Vec` node, going through the `Edge`s above it.
+    The subtree is returned as it is, with its own `Edge`s. It's `None` if an index
+    is out of bounds, or if a `Leaf` is reached before all the indices are used.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1"),
+        ErrorTree::leaf("error2").with_label("label"),
+    ]
+    .into();
+
+    assert!(matches!(tree.subtree_at(&[1]), Some(ErrorTree::Edge("label", _))));
+    assert!(tree.subtree_at(&[2]).is_none());
     ```
+    */
+    pub fn subtree_at(&self, indices: &[usize]) -> Option<&ErrorTree<L, E>> {
+        let mut tree = self;
+        for &index in indices {
+            while let ErrorTree::Edge(_, subtree) = tree {
+                tree = subtree;
+            }
+            match tree {
+                ErrorTree::Vec(trees) => tree = trees.get(index)?,
+                _ => return None,
+            }
+        }
+        Some(tree)
+    }
 
-    For `errors: Vec<E>`:
-    - It checks if `errors` is empty.
-    - If true, it will return `Ok(())`.
-    - Otherwise, it will return `Err(errors)`.
+    /**
+    Wraps the subtree at `indices`, selected as in `subtree_at`, in an `Edge` with `label`.
 
-    Since the trait is implemented for tuples of `(success: T, errors: Vec<E>)`
-    and for `Vec<E>`, it works well with `partition_result` from the `itertools` crate!
+    Returns `false` and leaves the tree unchanged if `indices` don't lead to a subtree.
 
     ```rust
-    # use itertools::*;
     # use error_trees::*;
-    struct Error(String);
+    let mut tree: ErrorTree<&'static str, &'static str> =
+        vec![ErrorTree::leaf("error1"), ErrorTree::leaf("error2")].into();
 
-    let error1 = ErrorTree::leaf(Error("first".into())).with_label("one");
-    let error2 = ErrorTree::leaf(Error("second".into())).with_label("two");
+    assert!(tree.insert_label_at(&[1], "label"));
 
-    let final_result: Result<_, ErrorTree<_, _>> = vec![error1, error2]
-        .into_result();
+    assert_eq!(tree.flatten_tree()[1].path, vec!["label"]);
     ```
     */
-    fn into_result(self) -> Result<T, E>;
-}
-
-impl<T, IE, E> IntoResult<T, E> for (T, Vec<IE>)
-where
-    Vec<IE>: Into<E>,
-{
-    fn into_result(self) -> Result<T, E> {
-        let (oks, errs) = self;
-        if errs.is_empty() {
-            Ok(oks)
-        } else {
-            Err(errs.into())
+    pub fn insert_label_at(&mut self, indices: &[usize], label: L) -> bool {
+        match self.subtree_at_mut(indices) {
+            Some(tree) => {
+                let subtree = std::mem::replace(tree, ErrorTree::Vec(Vec::new()));
+                *tree = ErrorTree::Edge(label, Box::new(subtree));
+                true
+            }
+            None => false,
         }
     }
-}
 
-impl<IE, E> IntoResult<(), E> for Vec<IE>
-where
-    Vec<IE>: Into<E>,
-{
-    fn into_result(self) -> Result<(), E> {
-        if self.is_empty() {
-            Ok(())
-        } else {
-            Err(self.into())
+    fn subtree_at_mut(&mut self, indices: &[usize]) -> Option<&mut ErrorTree<L, E>> {
+        match (self, indices.split_first()) {
+            (tree, None) => Some(tree),
+            (ErrorTree::Edge(_, tree), Some(_)) => tree.subtree_at_mut(indices),
+            (ErrorTree::Vec(trees), Some((index, rest))) => {
+                trees.get_mut(*index)?.subtree_at_mut(rest)
+            }
+            (ErrorTree::Leaf(_), Some(_)) => None,
         }
     }
-}
 
-/// Convenience trait to label errors within a `Result`.
-pub trait LabelResult<T, L, E> {
     /**
-    Maps a label to the `ErrorTree` within the result.
+    Keeps only the first error, with its labels, of each subtree of the top-level `Vec` node.
+
+    The top-level `Vec` node is the first one found going through the `Edge`s from the root,
+    whose labels are kept. The subtrees without errors are removed.
 
     ```rust
-    # use itertools::*;
     # use error_trees::*;
-    struct Error(String);
-    let result: Result<(), ErrorTree<&'static str, Error>> = Ok(());
-    let labeled_result = result.label_error("the label");
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::Vec(vec![ErrorTree::leaf("error1"), ErrorTree::leaf("error2")]).with_label("section1"),
+        ErrorTree::leaf("error3").with_label("section2"),
+    ]
+    .into();
+
+    let tree = tree.summarize_groups();
+
+    assert_eq!(tree.cloned_errors(), vec!["error1", "error3"]);
     ```
     */
-    fn label_error(self, label: L) -> Result<T, ErrorTree<L, E>>;
-}
-
-impl<T, L, E> LabelResult<T, L, E> for Result<T, E>
-where
-    ErrorTree<L, E>: From<E>,
-{
-    fn label_error(self, label: L) -> Result<T, ErrorTree<L, E>> {
-        self.map_err(|e| {
-            let tree: ErrorTree<L, E> = e.into();
-            tree.with_label(label)
-        })
+    pub fn summarize_groups(self) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Edge(label, tree) => ErrorTree::Edge(label, Box::new(tree.summarize_groups())),
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .filter_map(ErrorTree::into_first_branch)
+                    .collect_vec(),
+            ),
+            node => node.into(),
+        }
     }
-}
 
-impl<T, L, E> LabelResult<T, L, E> for Result<T, ErrorTree<L, E>> {
-    fn label_error(self, label: L) -> Result<T, ErrorTree<L, E>> {
-        self.map_err(|tree| tree.with_label(label))
+    /// Keeps only the first error of the tree, in the same order as `flatten_tree`, with its labels.
+    fn into_first_branch(self) -> Option<ErrorTree<L, E>> {
+        match self.into_node() {
+            Node::Leaf(error) => Some(ErrorTree::Leaf(error)),
+            Node::Edge(label, tree) => tree
+                .into_first_branch()
+                .map(|tree| ErrorTree::Edge(label, Box::new(tree))),
+            Node::Vec(trees) => trees.into_iter().find_map(ErrorTree::into_first_branch),
+        }
     }
-}
 
-pub trait FlattenResultErrors<T, L, E> {
-    fn flatten_results(self) -> Result<T, Vec<FlatError<L, E>>>;
-}
+    /**
+    Pairs every error with a sequential id, keeping the structure of the tree.
 
-impl<T, L, E> FlattenResultErrors<T, L, E> for Result<T, ErrorTree<L, E>>
-where
-    L: Clone,
-{
-    fn flatten_results(self) -> Result<T, Vec<FlatError<L, E>>> {
-        self.map_err(|tree| tree.flatten_tree())
-    }
-}
+    The ids start at `0` and follow the same order as `flatten_tree`.
 
-#[cfg(test)]
-mod tests {
-    use itertools::Itertools;
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
 
-    use super::*;
+    let tree = tree.assign_error_ids();
 
-    fn faulty(error: &str) -> Result<(), Error> {
-        Err(Error(error.into()))
+    assert_eq!(tree.cloned_errors(), vec![(0, "error1"), (1, "error2")]);
+    ```
+    */
+    pub fn assign_error_ids(self) -> ErrorTree<L, (usize, E)> {
+        self.assign_error_ids_with(&mut 0)
     }
 
-    #[test]
-    fn can_build_tree_from_vec_of_results() {
-        let result_1 = faulty("error1").map_err(|e| e.with_label("label1"));
-        let result_2 = faulty("error2").map_err(|e| e.with_label("label2"));
-
-        let (_, errors): (Vec<_>, Vec<_>) = vec![result_1, result_2].into_iter().partition_result();
+    fn assign_error_ids_with(self, next_id: &mut usize) -> ErrorTree<L, (usize, E)> {
+        match self.into_node() {
+            Node::Leaf(error) => {
+                let id = *next_id;
+                *next_id += 1;
+                ErrorTree::Leaf((id, error))
+            }
+            Node::Edge(label, tree) => {
+                ErrorTree::Edge(label, Box::new(tree.assign_error_ids_with(next_id)))
+            }
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.assign_error_ids_with(next_id))
+                    .collect_vec(),
+            ),
+        }
+    }
+
+    /**
+    Applies `f` to the subtrees of every `Vec` node.
+
+    The `Vec` nodes are visited in pre-order: every node is visited before the `Vec` nodes
+    nested in it, and sibling subtrees are visited from the first to the last.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    assert_eq!(tree.reduce_vecs(|group| group.len()), vec![2]);
+    ```
+    */
+    pub fn reduce_vecs<A, F: FnMut(&[ErrorTree<L, E>]) -> A>(&self, mut f: F) -> Vec<A> {
+        self.nodes()
+            .into_iter()
+            .filter_map(|tree| match tree {
+                ErrorTree::Vec(trees) => Some(f(trees)),
+                _ => None,
+            })
+            .collect_vec()
+    }
+
+    /**
+    Returns the errors with exactly `depth` labels above them.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    assert_eq!(tree.errors_at_depth(1), vec![&"error1"]);
+    ```
+    */
+    pub fn errors_at_depth(&self, depth: usize) -> Vec<&E> {
+        self.leaf_depths()
+            .into_iter()
+            .filter(|(leaf_depth, _)| *leaf_depth == depth)
+            .map(|(_, error)| error)
+            .collect_vec()
+    }
+
+    /**
+    Computes statistics over the number of labels above each error.
+
+    For a tree without errors, all the statistics are zero.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2").with_label("label2").with_label("label3"),
+    ]
+    .into();
+
+    let stats = tree.path_stats();
+
+    assert_eq!((stats.min, stats.max, stats.count), (1, 2, 2));
+    assert_eq!(stats.mean, 1.5);
+    ```
+    */
+    pub fn path_stats(&self) -> PathStats {
+        let depths = self.leaf_depths();
+        let count = depths.len();
+        let total: usize = depths.iter().map(|(depth, _)| depth).sum();
+        PathStats {
+            min: depths.iter().map(|(depth, _)| *depth).min().unwrap_or(0),
+            max: depths.iter().map(|(depth, _)| *depth).max().unwrap_or(0),
+            mean: if count == 0 {
+                0.0
+            } else {
+                total as f64 / count as f64
+            },
+            count,
+        }
+    }
+
+    /**
+    Counts the errors of the tree.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    assert_eq!(tree.count_leaves(), 2);
+    ```
+    */
+    pub fn count_leaves(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(tree) = stack.pop() {
+            match tree {
+                ErrorTree::Leaf(_) => count += 1,
+                ErrorTree::Edge(_, tree) => stack.push(tree),
+                ErrorTree::Vec(trees) => stack.extend(trees),
+            }
+        }
+        count
+    }
+
+    /**
+    Returns `true` if every error has at least one label above it.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        vec![ErrorTree::leaf("error1"), ErrorTree::leaf("error2").with_label("label")].into();
+
+    assert!(!tree.all_leaves_labeled());
+    ```
+    */
+    pub fn all_leaves_labeled(&self) -> bool {
+        self.leaf_depths().iter().all(|(depth, _)| *depth > 0)
+    }
+
+    /**
+    Returns the tree as an error if it has any errors, or `Ok(())` otherwise.
+
+    Empty `Vec` nodes don't count as errors, so an accumulated tree without errors is a success.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::Vec(vec![]);
+    assert!(tree.err_if_any().is_ok());
+
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error");
+    assert!(tree.err_if_any().is_err());
+    ```
+    */
+    pub fn err_if_any(self) -> Result<(), ErrorTree<L, E>> {
+        if self.count_leaves() > 0 {
+            Err(self)
+        } else {
+            Ok(())
+        }
+    }
+
+    /**
+    Counts the errors at each depth: the index `i` holds the number of errors with `i` labels above them.
+
+    The histogram is empty for a tree without errors.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2").with_label("label2"),
+        ErrorTree::leaf("error3").with_label("label3").with_label("label4"),
+    ]
+    .into();
+
+    assert_eq!(tree.depth_histogram(), vec![0, 2, 1]);
+    ```
+    */
+    pub fn depth_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for (depth, _) in self.leaf_depths() {
+            if histogram.len() <= depth {
+                histogram.resize(depth + 1, 0);
+            }
+            histogram[depth] += 1;
+        }
+        histogram
+    }
+
+    /// Lists the errors of the tree, with the number of labels above them, in the same order as `flatten_tree`.
+    fn leaf_depths(&self) -> Vec<(usize, &E)> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            match tree {
+                ErrorTree::Leaf(error) => leaves.push((depth, error)),
+                ErrorTree::Edge(_, tree) => stack.push((depth + 1, tree)),
+                ErrorTree::Vec(trees) => stack.extend(trees.iter().rev().map(|tree| (depth, tree))),
+            }
+        }
+        leaves
+    }
+
+    /// Lists the nodes of the tree in pre-order.
+    fn nodes(&self) -> Vec<&ErrorTree<L, E>> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![self];
+        while let Some(tree) = stack.pop() {
+            nodes.push(tree);
+            match tree {
+                ErrorTree::Leaf(_) => {}
+                ErrorTree::Edge(_, tree) => stack.push(tree),
+                ErrorTree::Vec(trees) => stack.extend(trees.iter().rev()),
+            }
+        }
+        nodes
+    }
+
+    /**
+    Iterates over the errors of the tree, with the labels above each error.
+
+    Unlike `flatten_tree`, the labels are borrowed instead of cloned.
+    The paths go from the root to the leaf.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    let paths: Vec<_> = tree.iter_paths().collect();
+
+    assert_eq!(paths, vec![(vec![&"parent_label", &"label"], &"error")]);
+    ```
+    */
+    pub fn iter_paths(&self) -> impl Iterator<Item = (Vec<&L>, &E)> {
+        PathIter {
+            path: Vec::new(),
+            stack: vec![(false, std::slice::from_ref(self).iter())],
+        }
+    }
+
+    /**
+    Iterates mutably over the errors of the tree, in the same order as `flatten_tree`.
+
+    ```rust
+    # use error_trees::*;
+    let mut tree: ErrorTree<&'static str, String> = vec![
+        ErrorTree::leaf("error1".to_string()).with_label("label1"),
+        ErrorTree::leaf("error2".to_string()),
+    ]
+    .into();
+
+    for error in tree.leaves_mut() {
+        error.push_str(" at 12:00");
+    }
+
+    assert_eq!(tree.flatten_tree()[1].error, "error2 at 12:00");
+    ```
+    */
+    pub fn leaves_mut(&mut self) -> impl Iterator<Item = &mut E> {
+        LeavesMut {
+            stack: vec![std::slice::from_mut(self).iter_mut()],
+        }
+    }
+
+    /**
+    Returns the error with the fewest labels above it, with its labels from the root.
+
+    If several errors have the same number of labels, the first one is returned.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1").with_label("label2"),
+        ErrorTree::leaf("error2").with_label("label3"),
+    ]
+    .into();
+
+    assert_eq!(tree.shallowest_error(), Some((vec![&"label3"], &"error2")));
+    ```
+    */
+    pub fn shallowest_error(&self) -> Option<(Vec<&L>, &E)> {
+        self.iter_paths().min_by_key(|(path, _)| path.len())
+    }
+
+    /**
+    Returns the error with the most labels above it, with its labels from the root.
+
+    The most specific context is a good guess for the root cause of the other errors.
+    If several errors have the same number of labels, the first one is returned.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2").with_label("label2").with_label("label3"),
+    ]
+    .into();
+
+    assert_eq!(tree.likely_root_cause(), Some((vec![&"label3", &"label2"], &"error2")));
+    ```
+    */
+    pub fn likely_root_cause(&self) -> Option<(Vec<&L>, &E)> {
+        self.iter_paths().reduce(|deepest, current| {
+            if current.0.len() > deepest.0.len() {
+                current
+            } else {
+                deepest
+            }
+        })
+    }
+
+    /**
+    Returns the error of the tree if it has exactly one, discarding its labels.
+    Otherwise, returns the tree.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    assert!(matches!(tree.into_single_or_tree(), Ok("error")));
+    ```
+    */
+    pub fn into_single_or_tree(self) -> Result<E, ErrorTree<L, E>> {
+        // The nodes above the current subtree, to rebuild the tree if it doesn't have a single error.
+        let mut layers = Vec::new();
+        let mut tree = self;
+        let tree = loop {
+            tree = match tree.into_node() {
+                Node::Leaf(error) => return Ok(error),
+                Node::Edge(label, tree) => {
+                    layers.push(Layer::Edge(label));
+                    *tree
+                }
+                Node::Vec(mut trees) => {
+                    let mut with_errors = trees
+                        .iter()
+                        .positions(|tree| tree.iter_paths().next().is_some());
+                    match (with_errors.next(), with_errors.next()) {
+                        (Some(index), None) => {
+                            let tree = trees.remove(index);
+                            layers.push(Layer::Vec(trees, index));
+                            tree
+                        }
+                        _ => break ErrorTree::Vec(trees),
+                    }
+                }
+            };
+        };
+        Err(layers
+            .into_iter()
+            .rev()
+            .fold(tree, |tree, layer| match layer {
+                Layer::Edge(label) => ErrorTree::Edge(label, Box::new(tree)),
+                Layer::Vec(mut trees, index) => {
+                    trees.insert(index, tree);
+                    ErrorTree::Vec(trees)
+                }
+            }))
+    }
+
+    /**
+    Returns the labels above the first error, from the outermost to the innermost, and the error itself.
+
+    Only the branch of the first error, in the same order as `flatten_tree`, is used:
+    the other errors are dropped. This fits reports built from a single error wrapped
+    in layers of context. It's `None` if the tree has no errors.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    let (labels, error) = tree.into_context_chain().unwrap();
+
+    assert_eq!(labels, vec!["parent_label", "label"]);
+    assert_eq!(error, "error");
+    ```
+    */
+    pub fn into_context_chain(self) -> Option<(Vec<L>, E)> {
+        let mut labels = Vec::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            labels.truncate(depth);
+            match tree.into_node() {
+                Node::Leaf(error) => return Some((labels, error)),
+                Node::Edge(label, tree) => {
+                    labels.push(label);
+                    stack.push((depth + 1, *tree));
+                }
+                Node::Vec(trees) => stack.extend(trees.into_iter().rev().map(|tree| (depth, tree))),
+            }
+        }
+        None
+    }
+
+    /**
+    Consumes the tree, calling `f` with the labels from the root and the error, for each error of the tree,
+    in the same order as `flatten_tree`.
+
+    Unlike `flatten_tree`, the labels aren't cloned: they are moved into a single buffer
+    shared by all the calls. Clone the path in `f` only when it must be kept.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<String, &'static str> =
+        ErrorTree::leaf("error").with_label("label".to_string());
+
+    let mut errors = Vec::new();
+    tree.flatten_each(|path, error| errors.push((path.len(), error)));
+
+    assert_eq!(errors, vec![(1, "error")]);
+    ```
+    */
+    pub fn flatten_each<F: FnMut(&[L], E)>(self, mut f: F) {
+        let mut path = Vec::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            path.truncate(depth);
+            match tree.into_node() {
+                Node::Leaf(error) => f(&path, error),
+                Node::Edge(label, tree) => {
+                    path.push(label);
+                    stack.push((depth + 1, *tree));
+                }
+                Node::Vec(trees) => stack.extend(trees.into_iter().rev().map(|tree| (depth, tree))),
+            }
+        }
+    }
+
+    /**
+    Creates a `Vec` tree from `trees`, checking it with `validate`.
+
+    ```rust
+    # use error_trees::*;
+    let tree = ErrorTree::<&'static str, &'static str>::try_vec(vec![]);
+
+    assert_eq!(tree.unwrap_err(), ErrorTreeError::EmptyTree);
+    ```
+    */
+    pub fn try_vec(trees: Vec<ErrorTree<L, E>>) -> Result<Self, ErrorTreeError> {
+        let tree = ErrorTree::Vec(trees);
+        tree.validate()?;
+        Ok(tree)
+    }
+
+    /**
+    Checks that the tree has errors, and that every label has errors below it.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::Vec(vec![]).with_label("label");
+
+    assert_eq!(tree.validate(), Err(ErrorTreeError::InvalidStructure));
+    ```
+    */
+    pub fn validate(&self) -> Result<(), ErrorTreeError> {
+        match self.count_checked_leaves()? {
+            0 => Err(ErrorTreeError::EmptyTree),
+            _ => Ok(()),
+        }
+    }
+
+    fn count_checked_leaves(&self) -> Result<usize, ErrorTreeError> {
+        match self {
+            ErrorTree::Leaf(_) => Ok(1),
+            ErrorTree::Edge(_, tree) => match tree.count_checked_leaves()? {
+                0 => Err(ErrorTreeError::InvalidStructure),
+                count => Ok(count),
+            },
+            ErrorTree::Vec(trees) => trees.iter().map(|tree| tree.count_checked_leaves()).sum(),
+        }
+    }
+
+    /**
+    Removes the outermost label of the tree.
+
+    If the root is an `Edge`, its subtree is returned, otherwise the tree is returned unchanged.
+    This is the inverse of `with_label`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    assert!(matches!(tree.strip_outer_label(), ErrorTree::Leaf("error")));
+    ```
+    */
+    pub fn strip_outer_label(self) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Edge(_, tree) => *tree,
+            node => node.into(),
+        }
+    }
+
+    /**
+    Removes the `Edge`s whose label matches `should_remove`, connecting their subtrees
+    directly to their parents.
+
+    The errors are kept, only the labels are removed from their paths.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("internal").with_label("public");
+
+    let flat_errors = tree.remove_labels(|label| *label == "internal").flatten_tree();
+
+    assert_eq!(flat_errors[0].path, vec!["public"]);
+    ```
+    */
+    pub fn remove_labels<F: FnMut(&L) -> bool>(self, mut should_remove: F) -> ErrorTree<L, E> {
+        self.remove_labels_with(&mut should_remove)
+    }
+
+    fn remove_labels_with<F: FnMut(&L) -> bool>(self, should_remove: &mut F) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Leaf(error) => ErrorTree::Leaf(error),
+            Node::Edge(label, tree) => {
+                if should_remove(&label) {
+                    tree.remove_labels_with(should_remove)
+                } else {
+                    ErrorTree::Edge(label, Box::new(tree.remove_labels_with(should_remove)))
+                }
+            }
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.remove_labels_with(should_remove))
+                    .collect_vec(),
+            ),
+        }
+    }
+
+    /**
+    Labels each error with a label computed from the error by `f`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<usize, &'static str> = ErrorTree::leaf("not found");
+
+    let flat_errors = tree.label_leaves_by(|error| error.len()).flatten_tree();
+
+    assert_eq!(flat_errors[0].path, vec![9]);
+    ```
+    */
+    pub fn label_leaves_by<F: FnMut(&E) -> L>(self, mut f: F) -> ErrorTree<L, E> {
+        self.label_leaves_with(&mut f)
+    }
+
+    fn label_leaves_with<F: FnMut(&E) -> L>(self, f: &mut F) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Leaf(error) => ErrorTree::Edge(f(&error), Box::new(ErrorTree::Leaf(error))),
+            Node::Edge(label, tree) => ErrorTree::Edge(label, Box::new(tree.label_leaves_with(f))),
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.label_leaves_with(f))
+                    .collect_vec(),
+            ),
+        }
+    }
+
+    /**
+    Limits the number of labels on each path to `max + 1`.
+
+    The first `max` labels of each path are kept. The remaining labels above each error
+    are replaced by a single label, built by `collapse` from the removed labels, from the root.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<String, &'static str> = ErrorTree::leaf("error")
+        .with_label("c".to_string())
+        .with_label("b".to_string())
+        .with_label("a".to_string());
+
+    let flat_errors = tree.cap_height(1, |labels| labels.join("/")).flatten_tree();
+
+    assert_eq!(flat_errors[0].path, vec!["b/c".to_string(), "a".to_string()]);
+    ```
+    */
+    pub fn cap_height<F: Fn(&[L]) -> L>(self, max: usize, collapse: F) -> ErrorTree<L, E> {
+        self.cap_height_at(0, max, &collapse)
+    }
+
+    fn cap_height_at<F: Fn(&[L]) -> L>(
+        self,
+        depth: usize,
+        max: usize,
+        collapse: &F,
+    ) -> ErrorTree<L, E> {
+        if depth >= max {
+            return self.collapse_labels(&mut Vec::new(), collapse);
+        }
+        match self.into_node() {
+            Node::Leaf(error) => ErrorTree::Leaf(error),
+            Node::Edge(label, tree) => ErrorTree::Edge(
+                label,
+                Box::new(tree.cap_height_at(depth + 1, max, collapse)),
+            ),
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.cap_height_at(depth, max, collapse))
+                    .collect_vec(),
+            ),
+        }
+    }
+
+    fn collapse_labels<F: Fn(&[L]) -> L>(
+        self,
+        labels: &mut Vec<L>,
+        collapse: &F,
+    ) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Leaf(error) if labels.is_empty() => ErrorTree::Leaf(error),
+            Node::Leaf(error) => {
+                ErrorTree::Edge(collapse(labels), Box::new(ErrorTree::Leaf(error)))
+            }
+            Node::Edge(label, tree) => {
+                labels.push(label);
+                let tree = tree.collapse_labels(labels, collapse);
+                labels.pop();
+                tree
+            }
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.collapse_labels(labels, collapse))
+                    .collect_vec(),
+            ),
+        }
+    }
+
+    /**
+    Rewrites the tree bottom-up with `f`.
+
+    The nodes are visited in post-order: `f` is applied to the subtrees of a node
+    before it is applied to the node itself, which already holds the rewritten subtrees.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, u32> = vec![ErrorTree::leaf(1), ErrorTree::leaf(2)].into();
+
+    let tree = tree.transform(|tree| match tree.into_node() {
+        Node::Leaf(error) => ErrorTree::Leaf(error * 10),
+        node => node.into(),
+    });
+
+    assert_eq!(tree.cloned_errors(), vec![10, 20]);
+    ```
+    */
+    pub fn transform<F>(self, mut f: F) -> ErrorTree<L, E>
+    where
+        F: FnMut(ErrorTree<L, E>) -> ErrorTree<L, E>,
+    {
+        self.transform_with(&mut f)
+    }
+
+    fn transform_with<F>(self, f: &mut F) -> ErrorTree<L, E>
+    where
+        F: FnMut(ErrorTree<L, E>) -> ErrorTree<L, E>,
+    {
+        let tree = match self.into_node() {
+            Node::Leaf(error) => ErrorTree::Leaf(error),
+            Node::Edge(label, tree) => ErrorTree::Edge(label, Box::new(tree.transform_with(f))),
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.transform_with(f))
+                    .collect_vec(),
+            ),
+        };
+        f(tree)
+    }
+
+    /**
+    Replaces every label with the result of `f`, for example to prefix labels with a namespace.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<String, &'static str> = ErrorTree::leaf("error").with_label("label".to_string());
+
+    let tree = tree.prefix_labels(|label| format!("A::{}", label));
+
+    assert_eq!(tree.flatten_tree()[0].path, vec!["A::label".to_string()]);
+    ```
+    */
+    pub fn prefix_labels<F: FnMut(L) -> L>(self, mut f: F) -> ErrorTree<L, E> {
+        self.transform(|tree| match tree.into_node() {
+            Node::Edge(label, tree) => ErrorTree::Edge(f(label), tree),
+            node => node.into(),
+        })
+    }
+
+    /**
+    Replaces each error for which `f` returns `Some(replacement)`, keeping the other errors and all the labels.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, String> = vec![
+        ErrorTree::leaf("invalid password: hunter2".to_string()).with_label("password"),
+        ErrorTree::leaf("too short".to_string()).with_label("name"),
+    ]
+    .into();
+
+    let tree = tree.redact(|error| {
+        error
+            .starts_with("invalid password")
+            .then(|| "invalid password".to_string())
+    });
+
+    assert_eq!(tree.cloned_errors(), vec!["invalid password", "too short"]);
+    ```
+    */
+    pub fn redact<F: FnMut(&E) -> Option<E>>(self, mut f: F) -> ErrorTree<L, E> {
+        self.transform(|tree| match tree.into_node() {
+            Node::Leaf(error) => ErrorTree::Leaf(f(&error).unwrap_or(error)),
+            node => node.into(),
+        })
+    }
+
+    /**
+    Replaces each error with the result of `f`, called with the labels above the error
+    from the root, and the error.
+
+    Useful to wrap each error in another error that holds its context.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, String> =
+        ErrorTree::leaf("error".to_string()).with_label("label");
+
+    let tree = tree.contextualize(|path, error| format!("{}: {}", path.join("."), error));
+
+    assert_eq!(tree.cloned_errors(), vec!["label: error"]);
+    ```
+    */
+    pub fn contextualize<F: FnMut(&[L], E) -> E>(self, mut f: F) -> ErrorTree<L, E> {
+        self.contextualize_with(&mut Vec::new(), &mut f)
+    }
+
+    fn contextualize_with<F: FnMut(&[L], E) -> E>(
+        self,
+        path: &mut Vec<L>,
+        f: &mut F,
+    ) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Leaf(error) => ErrorTree::Leaf(f(path, error)),
+            Node::Edge(label, tree) => {
+                path.push(label);
+                let tree = tree.contextualize_with(path, f);
+                match path.pop() {
+                    Some(label) => ErrorTree::Edge(label, Box::new(tree)),
+                    None => unreachable!("the label was pushed above"),
+                }
+            }
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.contextualize_with(path, f))
+                    .collect_vec(),
+            ),
+        }
+    }
+
+    /**
+    Replaces every label with the result of `f`, which borrows the label.
+
+    Useful to normalize labels, for example to group them regardless of their casing.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<String, &'static str> = ErrorTree::leaf("error").with_label("Label".to_string());
+
+    let tree = tree.normalize_labels(|label| label.to_lowercase());
+
+    assert_eq!(tree.flatten_tree()[0].path, vec!["label".to_string()]);
+    ```
+    */
+    pub fn normalize_labels<F: FnMut(&L) -> L>(self, mut f: F) -> ErrorTree<L, E> {
+        self.prefix_labels(|label| f(&label))
+    }
+
+    /**
+    Combines each run of sibling `Leaf`s in a `Vec` node into a single `Leaf`, reducing their errors with `f`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, String> = vec![
+        ErrorTree::leaf("missing a".to_string()),
+        ErrorTree::leaf("missing b".to_string()),
+    ]
+    .into();
+
+    let tree = tree.combine_sibling_leaves(|error1, error2| format!("{}, {}", error1, error2));
+
+    assert_eq!(tree.cloned_errors(), vec!["missing a, missing b"]);
+    ```
+    */
+    pub fn combine_sibling_leaves<F: FnMut(E, E) -> E>(self, mut f: F) -> ErrorTree<L, E> {
+        self.transform(|tree| match tree.into_node() {
+            Node::Vec(trees) => {
+                let mut combined = Vec::with_capacity(trees.len());
+                for tree in trees {
+                    match tree.into_node() {
+                        Node::Leaf(error) => match combined.pop().map(ErrorTree::into_node) {
+                            Some(Node::Leaf(previous)) => {
+                                combined.push(ErrorTree::Leaf(f(previous, error)))
+                            }
+                            previous => {
+                                combined.extend(previous.map(ErrorTree::from));
+                                combined.push(ErrorTree::Leaf(error));
+                            }
+                        },
+                        node => combined.push(node.into()),
+                    }
+                }
+                ErrorTree::Vec(combined)
+            }
+            node => node.into(),
+        })
+    }
+
+    /**
+    Merges the `Vec` nodes nested directly in other `Vec` nodes into their parents.
+
+    The `Edge`s and `Leaf`s are kept as they are.
+
+    ```rust
+    # use error_trees::*;
+    let inner: ErrorTree<&'static str, &'static str> = vec![ErrorTree::leaf("error1")].into();
+    let tree: ErrorTree<&'static str, &'static str> = vec![inner, ErrorTree::leaf("error2")].into();
+
+    let tree = tree.flatten_vecs();
+
+    assert!(matches!(&tree, ErrorTree::Vec(trees) if trees.len() == 2));
+    ```
+    */
+    pub fn flatten_vecs(self) -> ErrorTree<L, E> {
+        self.transform(|tree| match tree.into_node() {
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .flat_map(|tree| match tree.into_node() {
+                        Node::Vec(trees) => trees,
+                        node => vec![node.into()],
+                    })
+                    .collect_vec(),
+            ),
+            node => node.into(),
+        })
+    }
+}
+
+impl<E> ErrorTree<Location<'static>, E> {
+    /**
+    Creates a `Leaf` tree from an `error`, labeled with the location of the caller.
+
+    ```rust
+    # use error_trees::*;
+    struct Error(String);
+    let error_tree = ErrorTree::leaf_here(Error("error".into()));
+
+    let flat_errors = error_tree.flatten_tree();
+    assert_eq!(flat_errors[0].path[0].line(), line!() - 3);
+    ```
+    */
+    #[track_caller]
+    pub fn leaf_here(error: E) -> Self {
+        Self::leaf(error).with_label(*Location::caller())
+    }
+}
+
+/// A string label that only allocates when it is built from an owned `String`.
+///
+/// Cloning a borrowed `CowLabel`, like `flatten_tree` does, is as cheap as copying a `&'static str`.
+pub type CowLabel = Cow<'static, str>;
+
+impl<E> ErrorTree<CowLabel, E> {
+    /**
+    Adds a static string `label` to the error tree, without allocating.
+
+    ```rust
+    # use std::borrow::Cow;
+    # use error_trees::*;
+    struct Error(String);
+    let tree: ErrorTree<CowLabel, _> = ErrorTree::leaf(Error("error".into()))
+        .with_static_label("static label")
+        .with_label(Cow::Owned(format!("item {}", 1)));
+    ```
+    */
+    pub fn with_static_label(self, label: &'static str) -> Self {
+        self.with_label(Cow::Borrowed(label))
+    }
+}
+
+/**
+A label with a stable `key` and a `display` name, for example a localized one.
+
+It is displayed with its `display` name, but compared and hashed by its `key` only.
+
+```rust
+# use error_trees::*;
+let label = KeyedLabel::new("config", "Configuração");
+
+assert_eq!(label, KeyedLabel::new("config", "Configuration"));
+assert_eq!(label.to_string(), "Configuração");
+```
+*/
+#[derive(Debug, Clone)]
+pub struct KeyedLabel {
+    /// The stable key of the label.
+    pub key: &'static str,
+    /// The name of the label shown to the user.
+    pub display: String,
+}
+
+impl KeyedLabel {
+    /// Creates a label with a `key` and a `display` name.
+    pub fn new(key: &'static str, display: impl Into<String>) -> Self {
+        Self {
+            key,
+            display: display.into(),
+        }
+    }
+}
+
+impl Display for KeyedLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display)
+    }
+}
+
+impl PartialEq for KeyedLabel {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for KeyedLabel {}
+
+impl Hash for KeyedLabel {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+/// Statistics over the number of labels above each error, computed by `ErrorTree::path_stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathStats {
+    /// The smallest number of labels above an error.
+    pub min: usize,
+    /// The largest number of labels above an error.
+    pub max: usize,
+    /// The average number of labels above an error.
+    pub mean: f64,
+    /// The number of errors.
+    pub count: usize,
+}
+
+/// The expected structure of an `ErrorTree`, checked by `ErrorTree::matches_shape`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape<L> {
+    /// Matches any `Leaf`, whatever its error is.
+    AnyLeaf,
+    /// Matches an `Edge` with this label, over a subtree matching the shape.
+    Edge(L, Box<Shape<L>>),
+    /// Matches a `Vec` whose subtrees match these shapes, in order.
+    Vec(Vec<Shape<L>>),
+}
+
+/// The error returned by the fallible constructors of `ErrorTree`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorTreeError {
+    /// The tree has no errors.
+    EmptyTree,
+    /// The tree has a label without errors below it.
+    InvalidStructure,
+}
+
+impl Display for ErrorTreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorTreeError::EmptyTree => write!(f, "the error tree has no errors"),
+            ErrorTreeError::InvalidStructure => {
+                write!(f, "the error tree has a label without errors")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ErrorTreeError {}
+
+/// The id of a node, as assigned by `ErrorTree::assign_ids`.
+pub type NodeId = usize;
+
+/// The flattened error type
+#[derive(Debug)]
+pub struct FlatError<L, E> {
+    /// The path from the leaf to the root of the tree.
+    pub path: Vec<L>,
+    /// The error
+    pub error: E,
+}
+
+/// A flattened error with its position and depth, as built by `ErrorTree::detailed_flatten`.
+#[derive(Debug)]
+pub struct DetailedError<L, E> {
+    /// The position of the error, starting at zero, in the same order as `flatten_tree`.
+    pub index: usize,
+    /// The number of labels above the error.
+    pub depth: usize,
+    /// The path from the root of the tree to the leaf.
+    pub path: Vec<L>,
+    /// The error
+    pub error: E,
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Clone,
+{
+    /**
+    Flattens the error tree in a `Vec` of `FlatError`s.
+
+    The tree is traversed with an explicit stack, so very deep trees don't overflow the call stack.
+
+    ```rust
+    # use itertools::*;
+    # use error_trees::*;
+    #[derive(Debug)]
+    struct Error(String);
+
+    let error_1 = ErrorTree::leaf(Error("error1".into())).with_label("label1");
+    let error_2 = ErrorTree::leaf(Error("error2".into())).with_label("label2");
+
+    let errors = vec![error_1, error_2];
+
+    let tree: ErrorTree<&'static str, Error> = errors.into();
+    let tree = tree.with_label("parent_label");
+
+    let flat_errors = tree.flatten_tree();
+
+    assert!(
+        matches!(
+            &flat_errors[..],
+            [
+                FlatError {
+                    path: path1,
+                    error: Error(error1),
+                },
+                FlatError {
+                    path: path2,
+                    error: Error(error2),
+                },
+            ]
+            if path1 == &vec!["label1", "parent_label"]
+            && path2 == &vec!["label2", "parent_label"]
+            && error1 == "error1"
+            && error2 == "error2"
+        ),
+        "unexpected: {:#?}",
+        flat_errors
+    );
+    ```
+    */
+    pub fn flatten_tree(self) -> Vec<FlatError<L, E>> {
+        // Fast paths for the most common shapes, which need a single allocation.
+        match self.into_node() {
+            Node::Leaf(error) => vec![FlatError {
+                path: Vec::new(),
+                error,
+            }],
+            Node::Edge(label, tree) if matches!(*tree, ErrorTree::Leaf(_)) => {
+                let Node::Leaf(error) = tree.into_node() else {
+                    unreachable!()
+                };
+                vec![FlatError {
+                    path: vec![label],
+                    error,
+                }]
+            }
+            node => FlatIter::new(node.into()).collect_vec(),
+        }
+    }
+
+    /**
+    Returns the first `n` errors of the tree, in the same order as `flatten_tree`.
+
+    The tree is flattened lazily, so the traversal stops as soon as `n` errors were found.
+
+    ```rust
+    # use error_trees::*;
+    #[derive(Debug)]
+    struct Error(String);
+
+    let tree: ErrorTree<&'static str, Error> = vec![
+        ErrorTree::leaf(Error("error1".into())).with_label("label1"),
+        ErrorTree::leaf(Error("error2".into())).with_label("label2"),
+        ErrorTree::leaf(Error("error3".into())).with_label("label3"),
+    ]
+    .into();
+
+    let first_errors = tree.take_errors(2);
+
+    assert_eq!(first_errors.len(), 2);
+    assert_eq!(first_errors[1].path, vec!["label2"]);
+    ```
+    */
+    pub fn take_errors(self, n: usize) -> Vec<FlatError<L, E>> {
+        FlatIter::new(self).take(n).collect_vec()
+    }
+
+    /**
+    Calls `f` with the labels from the root and the error, for each error of the tree,
+    in the same order as `flatten_tree`.
+
+    The labels live in a single buffer shared by all the calls,
+    so no `Vec` is built for each error.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    let mut lines = Vec::new();
+    tree.for_each_leaf(|path, error| lines.push(format!("{}: {}", path.join("."), error)));
+
+    assert_eq!(lines, vec!["parent_label.label: error"]);
+    ```
+    */
+    pub fn for_each_leaf<F: FnMut(&[L], &E)>(&self, mut f: F) {
+        let mut path = Vec::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            path.truncate(depth);
+            match tree {
+                ErrorTree::Leaf(error) => f(&path, error),
+                ErrorTree::Edge(label, tree) => {
+                    path.push(label.clone());
+                    stack.push((depth + 1, tree));
+                }
+                ErrorTree::Vec(trees) => stack.extend(trees.iter().rev().map(|tree| (depth, tree))),
+            }
+        }
+    }
+
+    /**
+    Labels each subtree of a `Vec` node that has errors with `label`, and removes the subtrees without errors.
+
+    Any other tree is labeled as a whole if it has errors. This avoids labels without errors below them.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        vec![ErrorTree::leaf("error"), ErrorTree::Vec(vec![])].into();
+
+    let tree = tree.label_nonempty_children("item");
+
+    assert!(matches!(&tree, ErrorTree::Vec(trees) if trees.len() == 1));
+    ```
+    */
+    pub fn label_nonempty_children(self, label: L) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .filter(|tree| tree.count_leaves() > 0)
+                    .map(|tree| tree.with_label(label.clone()))
+                    .collect_vec(),
+            ),
+            node => {
+                let tree = ErrorTree::from(node);
+                if tree.count_leaves() > 0 {
+                    tree.with_label(label)
+                } else {
+                    tree
+                }
+            }
+        }
+    }
+
+    /**
+    Flattens the error tree into pages of `page_size` errors, in the same order as `flatten_tree`.
+
+    The last page holds the remaining errors, so it may be shorter.
+
+    # Panics
+
+    Panics if `page_size` is zero.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1"),
+        ErrorTree::leaf("error2"),
+        ErrorTree::leaf("error3"),
+    ]
+    .into();
+
+    let pages = tree.flatten_paged(2);
+
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[1][0].error, "error3");
+    ```
+    */
+    pub fn flatten_paged(self, page_size: usize) -> Vec<Vec<FlatError<L, E>>> {
+        assert!(page_size > 0, "page_size must be greater than zero");
+        FlatIter::new(self)
+            .chunks(page_size)
+            .into_iter()
+            .map(Iterator::collect)
+            .collect_vec()
+    }
+
+    /**
+    Flattens the error tree, pairing each `FlatError` with its position, starting at zero.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    for (index, flat_error) in tree.flatten_enumerated() {
+        println!("error #{} of 2: {}", index + 1, flat_error.error);
+    }
+    ```
+    */
+    pub fn flatten_enumerated(self) -> Vec<(usize, FlatError<L, E>)> {
+        FlatIter::new(self).enumerate().collect_vec()
+    }
+
+    /**
+    Splits the errors into the first one, as the primary error, and the other flattened errors, as notes.
+
+    This matches diagnostics made of one main error followed by notes.
+    It's `None` if the tree has no errors.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1"),
+        ErrorTree::leaf("error2").with_label("label"),
+    ]
+    .into();
+
+    let (primary, notes) = tree.into_primary_with_notes().unwrap();
+
+    assert_eq!(primary, "error1");
+    assert_eq!(notes[0].path, vec!["label"]);
+    ```
+    */
+    pub fn into_primary_with_notes(self) -> Option<(E, Vec<FlatError<L, E>>)> {
+        let mut errors = FlatIter::new(self);
+        let primary = errors.next()?;
+        Some((primary.error, errors.collect_vec()))
+    }
+
+    /**
+    Flattens the error tree into `DetailedError`s, with the position, the depth
+    and the root-to-leaf path of each error.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    let detailed = tree.with_label("parent_label").detailed_flatten();
+
+    assert_eq!(detailed[0].path, vec!["parent_label", "label1"]);
+    assert_eq!((detailed[1].index, detailed[1].depth), (1, 1));
+    ```
+    */
+    pub fn detailed_flatten(self) -> Vec<DetailedError<L, E>> {
+        FlatIter::new(self)
+            .enumerate()
+            .map(|(index, FlatError { mut path, error })| {
+                path.reverse();
+                DetailedError {
+                    index,
+                    depth: path.len(),
+                    path,
+                    error,
+                }
+            })
+            .collect_vec()
+    }
+
+    /**
+    Splits the tree into the `(fatal, recoverable)` errors, keeping their labels in both trees.
+
+    A tree is `None` if it would have no errors.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, u32> = vec![
+        ErrorTree::leaf(500).with_label("server"),
+        ErrorTree::leaf(404).with_label("client"),
+    ]
+    .into();
+
+    let (fatal, recoverable) = tree.triage(|status| *status >= 500);
+
+    assert_eq!(fatal.unwrap().cloned_errors(), vec![500]);
+    assert_eq!(recoverable.unwrap().cloned_errors(), vec![404]);
+    ```
+    */
+    pub fn triage<F: Fn(&E) -> bool>(self, is_fatal: F) -> (Option<Self>, Option<Self>) {
+        self.partition_with(&is_fatal)
+    }
+
+    fn partition_with<F: Fn(&E) -> bool>(self, f: &F) -> (Option<Self>, Option<Self>) {
+        match self.into_node() {
+            Node::Leaf(error) if f(&error) => (Some(ErrorTree::Leaf(error)), None),
+            Node::Leaf(error) => (None, Some(ErrorTree::Leaf(error))),
+            Node::Edge(label, tree) => match tree.partition_with(f) {
+                (Some(matching), Some(rest)) => (
+                    Some(ErrorTree::Edge(label.clone(), Box::new(matching))),
+                    Some(ErrorTree::Edge(label, Box::new(rest))),
+                ),
+                (matching, rest) => (
+                    matching.map(|tree| ErrorTree::Edge(label.clone(), Box::new(tree))),
+                    rest.map(|tree| ErrorTree::Edge(label, Box::new(tree))),
+                ),
+            },
+            Node::Vec(trees) => {
+                let (matching, rest): (Vec<_>, Vec<_>) =
+                    trees.into_iter().map(|tree| tree.partition_with(f)).unzip();
+                let matching = matching.into_iter().flatten().collect_vec();
+                let rest = rest.into_iter().flatten().collect_vec();
+                (
+                    (!matching.is_empty()).then(|| ErrorTree::Vec(matching)),
+                    (!rest.is_empty()).then(|| ErrorTree::Vec(rest)),
+                )
+            }
+        }
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: PartialEq,
+{
+    /**
+    Returns `true` if the tree has the structure and the labels described by `shape`, whatever its errors are.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    assert!(tree.matches_shape(&Shape::Edge("label", Box::new(Shape::AnyLeaf))));
+    ```
+    */
+    pub fn matches_shape(&self, shape: &Shape<L>) -> bool {
+        match (self, shape) {
+            (ErrorTree::Leaf(_), Shape::AnyLeaf) => true,
+            (ErrorTree::Edge(label, tree), Shape::Edge(expected, shape)) => {
+                label == expected && tree.matches_shape(shape)
+            }
+            (ErrorTree::Vec(trees), Shape::Vec(shapes)) => {
+                trees.len() == shapes.len()
+                    && trees
+                        .iter()
+                        .zip(shapes)
+                        .all(|(tree, shape)| tree.matches_shape(shape))
+            }
+            _ => false,
+        }
+    }
+
+    /**
+    Returns the error of a tree with a single error, if its labels from the root are `expected`.
+
+    Otherwise, the tree is returned unchanged.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    assert_eq!(tree.expect_path(&["parent_label", "label"]).ok(), Some("error"));
+    ```
+    */
+    pub fn expect_path(self, expected: &[L]) -> Result<E, ErrorTree<L, E>> {
+        if self
+            .iter_paths()
+            .all(|(path, _)| path.into_iter().eq(expected.iter()))
+        {
+            self.into_single_or_tree()
+        } else {
+            Err(self)
+        }
+    }
+
+    /**
+    Rebuilds a tree from flattened errors. This is the inverse of `flatten_tree`.
+
+    Consecutive errors with the same leading labels share the same `Edge`s,
+    so the errors keep their order when the tree is flattened again.
+
+    ```rust
+    # use error_trees::*;
+    let errors = vec![
+        FlatError { path: vec!["label1", "parent_label"], error: "error1" },
+        FlatError { path: vec!["label2", "parent_label"], error: "error2" },
+    ];
+
+    let tree = ErrorTree::unflatten(errors);
+
+    assert!(matches!(tree, ErrorTree::Edge("parent_label", _)));
+    ```
+    */
+    pub fn unflatten(errors: Vec<FlatError<L, E>>) -> ErrorTree<L, E> {
+        let mut trees = ErrorTree::unflatten_siblings(errors);
+        if trees.len() == 1 {
+            trees.remove(0)
+        } else {
+            ErrorTree::Vec(trees)
+        }
+    }
+
+    /**
+    Merges two trees, sharing their root `Edge` if both have the same outer label.
+
+    Otherwise, the trees are merged in a `Vec` node.
+
+    ```rust
+    # use error_trees::*;
+    let tree1: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error1").with_label("label");
+    let tree2 = ErrorTree::leaf("error2").with_label("label");
+
+    let merged = tree1.merge_sharing_root(tree2);
+
+    assert!(matches!(merged, ErrorTree::Edge("label", _)));
+    ```
+    */
+    pub fn merge_sharing_root(self, other: ErrorTree<L, E>) -> ErrorTree<L, E> {
+        match (self.into_node(), other.into_node()) {
+            (Node::Edge(label, tree), Node::Edge(other_label, other_tree))
+                if label == other_label =>
+            {
+                ErrorTree::Edge(label, Box::new(ErrorTree::Vec(vec![*tree, *other_tree])))
+            }
+            (tree, other) => ErrorTree::Vec(vec![tree.into(), other.into()]),
+        }
+    }
+
+    fn unflatten_siblings(errors: Vec<FlatError<L, E>>) -> Vec<ErrorTree<L, E>> {
+        let mut trees = Vec::new();
+        let mut errors = errors.into_iter().peekable();
+        while let Some(mut error) = errors.next() {
+            match error.path.pop() {
+                None => trees.push(ErrorTree::Leaf(error.error)),
+                Some(label) => {
+                    let mut group = vec![error];
+                    while let Some(mut next) =
+                        errors.next_if(|next| next.path.last() == Some(&label))
+                    {
+                        next.path.pop();
+                        group.push(next);
+                    }
+                    trees.push(ErrorTree::Edge(
+                        label,
+                        Box::new(ErrorTree::unflatten(group)),
+                    ));
+                }
+            }
+        }
+        trees
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Clone + PartialEq,
+{
+    /**
+    Returns the longest sequence of labels, from the root, shared by the paths of all errors.
+
+    The sequence is empty if the paths diverge at the root, or if the tree has no errors.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1").with_label("parent_label"),
+        ErrorTree::leaf("error2").with_label("label2").with_label("parent_label"),
+    ]
+    .into();
+
+    assert_eq!(tree.common_label_prefix(), vec!["parent_label"]);
+    ```
+    */
+    pub fn common_label_prefix(&self) -> Vec<L> {
+        let mut paths = self.iter_paths().map(|(path, _)| path);
+        let mut prefix = match paths.next() {
+            Some(path) => path.into_iter().cloned().collect_vec(),
+            None => return Vec::new(),
+        };
+        for path in paths {
+            let common = prefix.iter().zip(path).take_while(|(a, b)| a == b).count();
+            prefix.truncate(common);
+        }
+        prefix
+    }
+
+    /**
+    Hoists the labels shared by the paths of all errors into a single outer chain of `Edge`s,
+    removing the inner `Edge`s that repeated them.
+
+    The flattened paths are unchanged, only the structure of the tree gets smaller.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label"),
+        ErrorTree::leaf("error2").with_label("label"),
+    ]
+    .into();
+
+    let tree = tree.factor_common_prefix();
+
+    assert!(matches!(
+        &tree,
+        ErrorTree::Edge(label, tree) if *label == "label" && matches!(**tree, ErrorTree::Vec(_))
+    ));
+    ```
+    */
+    pub fn factor_common_prefix(self) -> ErrorTree<L, E> {
+        let prefix = self.common_label_prefix();
+        if prefix.is_empty() {
+            return self;
+        }
+        let stripped = self.strip_leading_labels(prefix.len());
+        prefix
+            .into_iter()
+            .rev()
+            .fold(stripped, |tree, label| tree.with_label(label))
+    }
+
+    /// Removes the first `n` labels from the path of every error.
+    fn strip_leading_labels(self, n: usize) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Edge(_, tree) if n > 0 => tree.strip_leading_labels(n - 1),
+            Node::Vec(trees) if n > 0 => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.strip_leading_labels(n))
+                    .collect_vec(),
+            ),
+            node => node.into(),
+        }
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Eq,
+{
+    /**
+    Returns `true` if the same label appears twice on a path from the root to a leaf.
+
+    This usually means that a recursive function labeled its errors more than once.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("label");
+
+    assert!(tree.has_repeated_label_on_path());
+    ```
+    */
+    pub fn has_repeated_label_on_path(&self) -> bool {
+        let mut path = Vec::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            path.truncate(depth);
+            match tree {
+                ErrorTree::Leaf(_) => {}
+                ErrorTree::Edge(label, tree) => {
+                    if path.contains(&label) {
+                        return true;
+                    }
+                    path.push(label);
+                    stack.push((depth + 1, tree));
+                }
+                ErrorTree::Vec(trees) => stack.extend(trees.iter().map(|tree| (depth, tree))),
+            }
+        }
+        false
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Clone + Ord,
+{
+    /**
+    Flattens the error tree, after sorting the subtrees of every `Vec` node by their outer label.
+
+    The sort is stable. Subtrees without an outer label (leaves and `Vec`s) come before
+    the labeled ones, and keep their relative order.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("b"),
+        ErrorTree::leaf("error2").with_label("a"),
+    ]
+    .into();
+
+    let flat_errors = tree.flatten_tree_sorted_by_label();
+
+    assert_eq!(flat_errors[0].path, vec!["a"]);
+    assert_eq!(flat_errors[1].path, vec!["b"]);
+    ```
+    */
+    pub fn flatten_tree_sorted_by_label(self) -> Vec<FlatError<L, E>> {
+        self.sort_by_label().flatten_tree()
+    }
+
+    fn sort_by_label(self) -> ErrorTree<L, E> {
+        match self.into_node() {
+            Node::Leaf(error) => ErrorTree::Leaf(error),
+            Node::Edge(label, tree) => ErrorTree::Edge(label, Box::new(tree.sort_by_label())),
+            Node::Vec(trees) => {
+                let mut trees = trees
+                    .into_iter()
+                    .map(|tree| tree.sort_by_label())
+                    .collect_vec();
+                trees.sort_by(|a, b| a.outer_label().cmp(&b.outer_label()));
+                ErrorTree::Vec(trees)
+            }
+        }
+    }
+
+    fn outer_label(&self) -> Option<&L> {
+        match self {
+            ErrorTree::Edge(label, _) => Some(label),
+            _ => None,
+        }
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    E: Clone,
+{
+    /**
+    Clones every error of the tree, without their labels, in the same order as `flatten_tree`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    assert_eq!(tree.cloned_errors(), vec!["error1", "error2"]);
+    ```
+    */
+    pub fn cloned_errors(&self) -> Vec<E> {
+        self.leaf_depths()
+            .into_iter()
+            .map(|(_, error)| error.clone())
+            .collect_vec()
+    }
+
+    /**
+    Attaches a shared `context` error to every error of the tree.
+
+    Each error is replaced by a `Vec` node with the error and a clone of `context`,
+    so the context has the same labels as each error.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("timeout").with_label("query");
+
+    let tree = tree.with_shared_context("database unavailable");
+
+    assert_eq!(tree.cloned_errors(), vec!["timeout", "database unavailable"]);
+    ```
+    */
+    pub fn with_shared_context(self, context: E) -> ErrorTree<L, E> {
+        self.transform(|tree| match tree.into_node() {
+            Node::Leaf(error) => ErrorTree::Vec(vec![
+                ErrorTree::Leaf(error),
+                ErrorTree::Leaf(context.clone()),
+            ]),
+            node => node.into(),
+        })
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    E: PartialEq,
+{
+    /**
+    Returns `true` if both trees have the same shape and the same errors, whatever their labels are.
+
+    ```rust
+    # use error_trees::*;
+    let tree1: ErrorTree<u64, &'static str> = ErrorTree::leaf("error").with_label(1700000000);
+    let tree2: ErrorTree<u64, &'static str> = ErrorTree::leaf("error").with_label(1700000042);
+
+    assert!(tree1.eq_ignoring_labels(&tree2));
+    ```
+    */
+    pub fn eq_ignoring_labels(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ErrorTree::Leaf(error1), ErrorTree::Leaf(error2)) => error1 == error2,
+            (ErrorTree::Edge(_, tree1), ErrorTree::Edge(_, tree2)) => {
+                tree1.eq_ignoring_labels(tree2)
+            }
+            (ErrorTree::Vec(trees1), ErrorTree::Vec(trees2)) => {
+                trees1.len() == trees2.len()
+                    && trees1
+                        .iter()
+                        .zip(trees2)
+                        .all(|(tree1, tree2)| tree1.eq_ignoring_labels(tree2))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Hash,
+    E: Hash,
+{
+    /**
+    Computes a digest of the structure, labels and errors of the tree.
+
+    The digest doesn't depend on memory addresses or on a random seed,
+    so equal trees have the same digest across runs of the same program.
+    Integers are hashed with a fixed width, little endian encoding, so the digest
+    doesn't depend on the platform either. It may change between Rust versions,
+    or versions of the label and error types, if their `Hash` implementations change.
+    The order of the subtrees in `Vec` nodes affects the digest.
+
+    ```rust
+    # use error_trees::*;
+    let tree1: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+    let tree2: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    assert_eq!(tree1.structural_digest(), tree2.structural_digest());
+    ```
+    */
+    pub fn structural_digest(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::default();
+        for tree in self.nodes() {
+            match tree {
+                ErrorTree::Leaf(error) => {
+                    0u8.hash(&mut hasher);
+                    error.hash(&mut hasher);
+                }
+                ErrorTree::Edge(label, _) => {
+                    1u8.hash(&mut hasher);
+                    label.hash(&mut hasher);
+                }
+                ErrorTree::Vec(trees) => {
+                    2u8.hash(&mut hasher);
+                    hasher.write(&(trees.len() as u64).to_le_bytes());
+                }
+            }
+        }
+        hasher.finish()
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Eq + Hash,
+{
+    /**
+    Counts the distinct label paths that lead to errors.
+
+    Errors with the same labels above them count once.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label"),
+        ErrorTree::leaf("error2").with_label("label"),
+    ]
+    .into();
+
+    assert_eq!(tree.distinct_path_count(), 1);
+    ```
+    */
+    pub fn distinct_path_count(&self) -> usize {
+        self.iter_paths()
+            .map(|(path, _)| path)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /**
+    Returns every label used in the tree, once each.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label"),
+        ErrorTree::leaf("error2").with_label("label"),
+    ]
+    .into();
+
+    assert_eq!(tree.distinct_labels().len(), 1);
+    ```
+    */
+    pub fn distinct_labels(&self) -> HashSet<&L> {
+        self.nodes()
+            .into_iter()
+            .filter_map(|tree| match tree {
+                ErrorTree::Edge(label, _) => Some(label),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /**
+    Returns `true` if both trees use at least one label in common.
+
+    ```rust
+    # use error_trees::*;
+    let tree1: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error1").with_label("email");
+    let tree2: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error2").with_label("email");
+
+    assert!(tree1.shares_label_with(&tree2));
+    ```
+    */
+    pub fn shares_label_with(&self, other: &ErrorTree<L, E>) -> bool {
+        let labels = self.distinct_labels();
+        !labels.is_disjoint(&other.distinct_labels())
+    }
+
+    /**
+    Converts the tree into nested maps keyed by label, merging the siblings with the same label.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label"),
+        ErrorTree::leaf("error2").with_label("label"),
+        ErrorTree::leaf("error3"),
+    ]
+    .into();
+
+    let nested = tree.to_nested_map();
+
+    assert_eq!(nested.errors, vec!["error3"]);
+    assert_eq!(nested.branches["label"].errors, vec!["error1", "error2"]);
+    ```
+    */
+    pub fn to_nested_map(self) -> NestedErrors<L, E> {
+        let mut nested = NestedErrors::default();
+        self.insert_nested(&mut nested);
+        nested
+    }
+
+    fn insert_nested(self, nested: &mut NestedErrors<L, E>) {
+        match self.into_node() {
+            Node::Leaf(error) => nested.errors.push(error),
+            Node::Edge(label, tree) => {
+                tree.insert_nested(nested.branches.entry(label).or_default())
+            }
+            Node::Vec(trees) => {
+                for tree in trees {
+                    tree.insert_nested(nested);
+                }
+            }
+        }
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    E: Eq + Hash,
+{
+    /**
+    Returns the error that appears most often in the tree, with the number of times it appears.
+
+    If several errors appear the same number of times, the first one is returned.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("timeout").with_label("a"),
+        ErrorTree::leaf("not found").with_label("b"),
+        ErrorTree::leaf("timeout").with_label("c"),
+    ]
+    .into();
+
+    assert_eq!(tree.most_common_error(), Some((&"timeout", 2)));
+    ```
+    */
+    pub fn most_common_error(&self) -> Option<(&E, usize)> {
+        let errors = self.leaf_depths();
+        let mut counts = HashMap::new();
+        for (_, error) in &errors {
+            *counts.entry(*error).or_insert(0) += 1;
+        }
+        let mut most_common: Option<(&E, usize)> = None;
+        for (_, error) in errors {
+            let count = counts[error];
+            match most_common {
+                Some((_, max)) if count <= max => {}
+                _ => most_common = Some((error, count)),
+            }
+        }
+        most_common
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    E: Clone + Eq + Hash,
+{
+    /**
+    Replaces each error with its index in a table of distinct errors, returned alongside the tree.
+
+    Equal errors share one entry of the table, in the order they first appear in `flatten_tree`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("timeout").with_label("a"),
+        ErrorTree::leaf("timeout").with_label("b"),
+    ]
+    .into();
+
+    let (tree, errors) = tree.intern_errors();
+
+    assert_eq!(errors, vec!["timeout"]);
+    assert_eq!(tree.cloned_errors(), vec![0, 0]);
+    ```
+    */
+    pub fn intern_errors(self) -> (ErrorTree<L, usize>, Vec<E>) {
+        let mut indices = HashMap::new();
+        let mut errors = Vec::new();
+        let tree = self.intern_errors_with(&mut indices, &mut errors);
+        (tree, errors)
+    }
+
+    fn intern_errors_with(
+        self,
+        indices: &mut HashMap<E, usize>,
+        errors: &mut Vec<E>,
+    ) -> ErrorTree<L, usize> {
+        match self.into_node() {
+            Node::Leaf(error) => {
+                let index = *indices.entry(error).or_insert_with_key(|error| {
+                    errors.push(error.clone());
+                    errors.len() - 1
+                });
+                ErrorTree::Leaf(index)
+            }
+            Node::Edge(label, tree) => {
+                ErrorTree::Edge(label, Box::new(tree.intern_errors_with(indices, errors)))
+            }
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .map(|tree| tree.intern_errors_with(indices, errors))
+                    .collect_vec(),
+            ),
+        }
+    }
+}
+
+/// The errors of an `ErrorTree` in nested maps keyed by label, built by `ErrorTree::to_nested_map`.
+///
+/// A level of a tree can have both errors and labeled subtrees, so each level keeps both.
+#[derive(Debug)]
+pub struct NestedErrors<L, E> {
+    /// The errors without further labels at this level.
+    pub errors: Vec<E>,
+    /// The errors under each label at this level.
+    pub branches: HashMap<L, NestedErrors<L, E>>,
+}
+
+impl<L, E> Default for NestedErrors<L, E> {
+    fn default() -> Self {
+        Self {
+            errors: Vec::new(),
+            branches: HashMap::new(),
+        }
+    }
+}
+
+/// The errors that differ between two trees, as built by `ErrorTree::diff`.
+#[derive(Debug)]
+pub struct TreeDiff<L, E> {
+    /// The errors found only in the first tree.
+    pub only_in_self: Vec<FlatError<L, E>>,
+    /// The errors found only in the second tree.
+    pub only_in_other: Vec<FlatError<L, E>>,
+    /// The errors found in both trees.
+    pub in_both: Vec<FlatError<L, E>>,
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Eq + Clone,
+    E: Eq + Clone,
+{
+    /**
+    Compares the errors of two trees, with their paths.
+
+    An error repeated in a tree is matched at most once for each copy in the other tree.
+    Each list keeps the same order as `flatten_tree`.
+
+    ```rust
+    # use error_trees::*;
+    let before: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2").with_label("label2"),
+    ]
+    .into();
+    let after: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error2").with_label("label2"),
+        ErrorTree::leaf("error3").with_label("label3"),
+    ]
+    .into();
+
+    let diff = after.diff(&before);
+
+    assert_eq!(diff.only_in_self[0].error, "error3");
+    assert_eq!(diff.only_in_other[0].error, "error1");
+    assert_eq!(diff.in_both[0].error, "error2");
+    ```
+    */
+    pub fn diff(&self, other: &Self) -> TreeDiff<L, E> {
+        let mut others = other
+            .cloned_flat_errors()
+            .into_iter()
+            .map(Some)
+            .collect_vec();
+        let mut diff = TreeDiff {
+            only_in_self: Vec::new(),
+            only_in_other: Vec::new(),
+            in_both: Vec::new(),
+        };
+        for flat_error in self.cloned_flat_errors() {
+            let matching = others.iter_mut().find(|other| {
+                other.as_ref().is_some_and(|other| {
+                    other.path == flat_error.path && other.error == flat_error.error
+                })
+            });
+            match matching {
+                Some(other) => {
+                    *other = None;
+                    diff.in_both.push(flat_error);
+                }
+                None => diff.only_in_self.push(flat_error),
+            }
+        }
+        diff.only_in_other = others.into_iter().flatten().collect_vec();
+        diff
+    }
+
+    fn cloned_flat_errors(&self) -> Vec<FlatError<L, E>> {
+        self.iter_paths()
+            .map(|(path, error)| FlatError {
+                path: path.into_iter().rev().cloned().collect_vec(),
+                error: error.clone(),
+            })
+            .collect_vec()
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Clone + Eq + Hash,
+{
+    /**
+    Flattens the error tree, keeping only the first error of each distinct label path.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label"),
+        ErrorTree::leaf("error2").with_label("label"),
+    ]
+    .into();
+
+    let flat_errors = tree.first_per_path();
+
+    assert_eq!(flat_errors.len(), 1);
+    assert_eq!(flat_errors[0].error, "error1");
+    ```
+    */
+    pub fn first_per_path(self) -> Vec<FlatError<L, E>> {
+        let mut seen = HashSet::new();
+        let is_first = self
+            .iter_paths()
+            .map(|(path, _)| seen.insert(path))
+            .collect_vec();
+        self.flatten_tree()
+            .into_iter()
+            .zip(is_first)
+            .filter_map(|(error, is_first)| is_first.then_some(error))
+            .collect_vec()
+    }
+}
+
+/// The 64 bit FNV-1a hash function, which, unlike `DefaultHasher`, has no random seed.
+///
+/// Integers are written as fixed width little endian bytes, and `usize`s and `isize`s as 64 bits,
+/// so the same values hash the same on every platform.
+struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+/// A node taken apart while descending into one of its subtrees.
+enum Layer<L, E> {
+    /// The label of an `Edge`.
+    Edge(L),
+    /// The other subtrees of a `Vec`, and the index of the removed one.
+    Vec(Vec<ErrorTree<L, E>>, usize),
+}
+
+/// Lazily flattens an `ErrorTree`, yielding `FlatError`s in the same order as `flatten_tree`.
+struct FlatIter<L, E> {
+    /// The labels of the `Edge`s above the current node, from the root.
+    path: Vec<L>,
+    /// The subtrees not visited yet. The boolean tells if the frame opened an `Edge`.
+    stack: Vec<(bool, std::vec::IntoIter<ErrorTree<L, E>>)>,
+}
+
+impl<L, E> FlatIter<L, E> {
+    fn new(tree: ErrorTree<L, E>) -> Self {
+        Self {
+            path: Vec::new(),
+            stack: vec![(false, vec![tree].into_iter())],
+        }
+    }
+}
+
+impl<L, E> Iterator for FlatIter<L, E>
+where
+    L: Clone,
+{
+    type Item = FlatError<L, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (_, subtrees) = self.stack.last_mut()?;
+            match subtrees.next().map(ErrorTree::into_node) {
+                None => {
+                    if let Some((true, _)) = self.stack.pop() {
+                        self.path.pop();
+                    }
+                }
+                Some(Node::Leaf(error)) => {
+                    return Some(FlatError {
+                        path: self.path.iter().rev().cloned().collect_vec(),
+                        error,
+                    })
+                }
+                Some(Node::Edge(label, tree)) => {
+                    self.path.push(label);
+                    self.stack.push((true, vec![*tree].into_iter()));
+                }
+                Some(Node::Vec(trees)) => {
+                    self.stack.push((false, trees.into_iter()));
+                }
+            }
+        }
+    }
+}
+
+/// Displays each error of an `ErrorTree` in its own line, after its labels.
+///
+/// Built by `ErrorTree::displayed`.
+pub struct Displayed<'a, L, E>(pub &'a ErrorTree<L, E>);
+
+impl<L, E> Display for Displayed<'_, L, E>
+where
+    L: Display,
+    E: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (path, error)) in self.0.iter_paths().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for (j, label) in path.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " > ")?;
+                }
+                write!(f, "{}", label)?;
+            }
+            if path.is_empty() {
+                write!(f, "{}", error)?;
+            } else {
+                write!(f, ": {}", error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A flattened error whose labels are joined in a single `String`, as built by `ErrorTree::flatten_display`.
+#[derive(Debug)]
+pub struct FlatDisplayError<E> {
+    /// The labels from the root to the leaf, joined by a separator.
+    pub path: String,
+    /// The error
+    pub error: E,
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Display,
+{
+    /**
+    Flattens the error tree, joining the labels of each error from the root with `sep`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    let flat_errors = tree.flatten_display(".");
+
+    assert_eq!(flat_errors[0].path, "parent_label.label");
+    ```
+    */
+    pub fn flatten_display(self, sep: &str) -> Vec<FlatDisplayError<E>> {
+        let mut flat_errors = Vec::new();
+        self.flatten_each(|path, error| {
+            flat_errors.push(FlatDisplayError {
+                path: path.iter().join(sep),
+                error,
+            })
+        });
+        flat_errors
+    }
+}
+
+/// An error with plain owned strings, as built by `ErrorTree::to_ffi_errors`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FfiError {
+    /// The labels from the root to the leaf, joined by ` > `.
+    pub path: String,
+    /// The displayed error.
+    pub message: String,
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Display,
+    E: Display,
+{
+    /**
+    Returns a value that displays each error in its own line,
+    using the `Display` of the error, after its labels from the root.
+
+    This works well with error enums that derive `Display` with `thiserror`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+    let tree = tree.with_label("parent_label");
+
+    assert_eq!(
+        tree.displayed().to_string(),
+        "parent_label > label1: error1\nparent_label: error2"
+    );
+    ```
+    */
+    pub fn displayed(&self) -> Displayed<'_, L, E> {
+        Displayed(self)
+    }
+
+    /**
+    Converts the tree into two-column rows, with the labels of each error
+    from the root joined by `sep`, and the error.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    assert_eq!(
+        tree.to_rows(" > "),
+        vec![["parent_label > label".to_string(), "error".to_string()]]
+    );
+    ```
+    */
+    pub fn to_rows(&self, sep: &str) -> Vec<[String; 2]> {
+        self.iter_paths()
+            .map(|(path, error)| [path.iter().join(sep), error.to_string()])
+            .collect_vec()
+    }
+
+    /**
+    Renders every error of the tree on a single line, between brackets.
+
+    The labels of each error are joined by `>` from the root, followed by `kv_sep` and the error.
+    The errors are joined by `pair_sep`. Errors without labels are written alone.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("err1").with_label("b"),
+        ErrorTree::leaf("err2").with_label("c"),
+    ]
+    .into();
+    let tree = tree.with_label("a");
+
+    assert_eq!(tree.to_compact_string("; ", ": "), "[a>b: err1; a>c: err2]");
+    ```
+    */
+    pub fn to_compact_string(&self, pair_sep: &str, kv_sep: &str) -> String {
+        let pairs = self.iter_paths().map(|(path, error)| {
+            if path.is_empty() {
+                error.to_string()
+            } else {
+                format!("{}{}{}", path.iter().join(">"), kv_sep, error)
+            }
+        });
+        format!("[{}]", pairs.format(pair_sep))
+    }
+
+    /**
+    Replaces the labels above each error with a single label, the labels from the root joined by `sep`.
+
+    The result is a `Vec` node with one subtree per error, in the same order as `flatten_tree`.
+    The errors without labels stay unlabeled.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    let tree = tree.collapse_paths(".");
+
+    assert_eq!(tree.flatten_tree()[0].path, vec!["parent_label.label".to_string()]);
+    ```
+    */
+    pub fn collapse_paths(self, sep: &str) -> ErrorTree<String, E> {
+        let mut trees = Vec::new();
+        let mut labels: Vec<String> = Vec::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            labels.truncate(depth);
+            match tree.into_node() {
+                Node::Leaf(error) if labels.is_empty() => trees.push(ErrorTree::Leaf(error)),
+                Node::Leaf(error) => {
+                    trees.push(ErrorTree::leaf(error).with_label(labels.join(sep)))
+                }
+                Node::Edge(label, tree) => {
+                    labels.push(label.to_string());
+                    stack.push((depth + 1, *tree));
+                }
+                Node::Vec(subtrees) => {
+                    stack.extend(subtrees.into_iter().rev().map(|tree| (depth, tree)))
+                }
+            }
+        }
+        ErrorTree::Vec(trees)
+    }
+
+    /**
+    Converts every error into an `FfiError`, with plain owned strings that don't depend on `L` nor `E`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    assert_eq!(
+        tree.to_ffi_errors(),
+        vec![FfiError {
+            path: "parent_label > label".to_string(),
+            message: "error".to_string(),
+        }]
+    );
+    ```
+    */
+    pub fn to_ffi_errors(&self) -> Vec<FfiError> {
+        self.iter_paths()
+            .map(|(path, error)| FfiError {
+                path: path.iter().join(" > "),
+                message: error.to_string(),
+            })
+            .collect_vec()
+    }
+
+    /**
+    Renders every error in its own line, after its labels from the root joined by `sep`.
+
+    Meant as the error of a small `main`, returning `Result<(), String>`.
+
+    ```rust
+    # use error_trees::*;
+    fn run() -> Result<(), String> {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        tree.err_if_any().map_err(|tree| tree.into_string_error("/"))
+    }
+
+    assert_eq!(run().unwrap_err(), "label1: error1\nerror2");
+    ```
+    */
+    pub fn into_string_error(self, sep: &str) -> String {
+        self.iter_paths()
+            .map(|(path, error)| {
+                if path.is_empty() {
+                    error.to_string()
+                } else {
+                    format!("{}: {}", path.iter().join(sep), error)
+                }
+            })
+            .join("\n")
+    }
+
+    /**
+    Renders the tree as CSV, with the fields separated by `sep`.
+
+    The first row is the header `path` and `error`. Then each error has a row with its labels
+    from the root joined by ` > `, and the error. The fields containing `sep`, quotes or
+    line breaks are quoted, with their quotes doubled.
+
+    # Panics
+
+    Panics if `sep` is empty.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("expected \"a\", got \"b\"").with_label("field");
+
+    assert_eq!(
+        tree.to_csv(","),
+        "path,error\nfield,\"expected \"\"a\"\", got \"\"b\"\"\"\n"
+    );
+    ```
+    */
+    pub fn to_csv(&self, sep: &str) -> String {
+        assert!(!sep.is_empty(), "sep must not be empty");
+        let mut csv = format!("path{}error\n", sep);
+        for (path, error) in self.iter_paths() {
+            write_csv_field(&path.iter().join(" > "), sep, &mut csv);
+            csv.push_str(sep);
+            write_csv_field(&error.to_string(), sep, &mut csv);
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /**
+    Renders the tree as nested Markdown lists.
+
+    Labels become parent bullets and errors become leaf bullets,
+    indented by two spaces per level.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+    let tree = tree.with_label("parent_label");
+
+    assert_eq!(
+        tree.to_markdown(),
+        "- parent_label\n  - label1\n    - error1\n  - error2\n"
+    );
+    ```
+    */
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        self.write_markdown(0, &mut markdown);
+        markdown
+    }
+
+    /**
+    Renders the tree as indented text.
+
+    Labels and errors are written on their own lines, indented by two spaces per label above them.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    assert_eq!(tree.render_tree(), "label1\n  error1\nerror2\n");
+    ```
+    */
+    pub fn render_tree(&self) -> String {
+        let mut rendered = String::with_capacity(self.estimated_render_len());
+        // Writing to a `String` never fails.
+        let _ = self.write_tree(&mut rendered);
+        rendered
+    }
+
+    /**
+    Estimates the length of `render_tree`, to allocate its output up front.
+
+    The labels and errors are not formatted, so only the indentation and line breaks
+    are counted. The estimate is at most the length of the rendered tree.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    assert_eq!(tree.estimated_render_len(), "\n  \n".len());
+    assert!(tree.estimated_render_len() <= tree.render_tree().len());
+    ```
+    */
+    pub fn estimated_render_len(&self) -> usize {
+        let mut len = 0;
+        self.for_each_line(|depth, _| len += 2 * depth + 1);
+        len
+    }
+
+    /**
+    Renders the tree as indented text, like `render_tree`, repeating `indent` once per label above each line.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> =
+        ErrorTree::leaf("error").with_label("label").with_label("parent_label");
+
+    assert_eq!(tree.render_tree_with("|-- "), "parent_label\n|-- label\n|-- |-- error\n");
+    ```
+    */
+    pub fn render_tree_with(&self, indent: &str) -> String {
+        use std::fmt::Write;
+
+        let mut rendered = String::new();
+        self.for_each_line(|depth, line| {
+            for _ in 0..depth {
+                rendered.push_str(indent);
+            }
+            // Writing to a `String` never fails.
+            let _ = writeln!(rendered, "{}", line);
+        });
+        rendered
+    }
+
+    /**
+    Writes the same indented text as `render_tree` directly to `w`, without building a `String`.
+
+    ```rust
+    # use std::fmt::Write;
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    let mut rendered = String::new();
+    tree.write_tree(&mut rendered).unwrap();
+
+    assert_eq!(rendered, "label\n  error\n");
+    ```
+    */
+    pub fn write_tree<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+        let mut result = Ok(());
+        self.for_each_line(|depth, line| {
+            if result.is_ok() {
+                result = writeln!(w, "{:indent$}{}", "", line, indent = 2 * depth);
+            }
+        });
+        result
+    }
+
+    /**
+    Renders the tree as indented text, with at most `max_lines` lines.
+
+    Labels and errors are written on their own lines, indented by two spaces per label above them.
+    If the tree has more lines, a last line `... (N more)` tells how many lines were left out.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2").with_label("label2"),
+    ]
+    .into();
+
+    assert_eq!(
+        tree.render_truncated(3),
+        "label1\n  error1\nlabel2\n... (1 more)\n"
+    );
+    ```
+    */
+    pub fn render_truncated(&self, max_lines: usize) -> String {
+        use std::fmt::Write;
+
+        let mut rendered = String::new();
+        let mut lines = 0;
+        // Writing to a `String` never fails.
+        self.for_each_line(|depth, line| {
+            if lines < max_lines {
+                let _ = writeln!(rendered, "{:indent$}{}", "", line, indent = 2 * depth);
+            }
+            lines += 1;
+        });
+        if lines > max_lines {
+            let _ = writeln!(rendered, "... ({} more)", lines - max_lines);
+        }
+        rendered
+    }
+
+    /// Calls `f` with the depth and the content of each line of the indented rendering.
+    fn for_each_line<F: FnMut(usize, &dyn Display)>(&self, mut f: F) {
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            match tree {
+                ErrorTree::Leaf(error) => f(depth, error),
+                ErrorTree::Edge(label, tree) => {
+                    f(depth, label);
+                    stack.push((depth + 1, tree));
+                }
+                ErrorTree::Vec(trees) => stack.extend(trees.iter().rev().map(|tree| (depth, tree))),
+            }
+        }
+    }
+
+    fn write_markdown(&self, depth: usize, markdown: &mut String) {
+        match self {
+            ErrorTree::Leaf(error) => {
+                markdown.push_str(&format!("{}- {}\n", "  ".repeat(depth), error));
+            }
+            ErrorTree::Edge(label, tree) => {
+                markdown.push_str(&format!("{}- {}\n", "  ".repeat(depth), label));
+                tree.write_markdown(depth + 1, markdown);
+            }
+            ErrorTree::Vec(trees) => {
+                for tree in trees {
+                    tree.write_markdown(depth, markdown);
+                }
+            }
+        }
+    }
+
+    /**
+    Renders the tree as JSON, without depending on `serde`.
+
+    - A `Leaf` becomes `{"error": "..."}`.
+    - An `Edge` becomes `{"label": "...", "children": [...]}`, with its subtree as the only child.
+    - A `Vec` becomes `{"children": [...]}`.
+
+    Labels and errors are rendered with `Display` as JSON strings.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error").with_label("label");
+
+    assert_eq!(
+        tree.to_json(),
+        r#"{"label":"label","children":[{"error":"error"}]}"#
+    );
+    ```
+    */
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        match self {
+            ErrorTree::Leaf(error) => {
+                json.push_str("{\"error\":");
+                write_json_string(&error.to_string(), json);
+                json.push('}');
+            }
+            ErrorTree::Edge(label, tree) => {
+                json.push_str("{\"label\":");
+                write_json_string(&label.to_string(), json);
+                json.push_str(",\"children\":[");
+                tree.write_json(json);
+                json.push_str("]}");
+            }
+            ErrorTree::Vec(trees) => {
+                json.push_str("{\"children\":[");
+                for (i, tree) in trees.iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    tree.write_json(json);
+                }
+                json.push_str("]}");
+            }
+        }
+    }
+}
+
+fn write_json_string(s: &str, json: &mut String) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn write_csv_field(field: &str, sep: &str, csv: &mut String) {
+    if field.contains(sep) || field.contains(['"', '\n', '\r']) {
+        csv.push('"');
+        csv.push_str(&field.replace('"', "\"\""));
+        csv.push('"');
+    } else {
+        csv.push_str(field);
+    }
+}
+
+/// Iterates over the errors of an `ErrorTree`, borrowing the labels above them.
+struct PathIter<'a, L, E> {
+    /// The labels of the `Edge`s above the current node, from the root.
+    path: Vec<&'a L>,
+    /// The subtrees not visited yet. The boolean tells if the frame opened an `Edge`.
+    stack: Vec<(bool, std::slice::Iter<'a, ErrorTree<L, E>>)>,
+}
+
+impl<'a, L, E> Iterator for PathIter<'a, L, E> {
+    type Item = (Vec<&'a L>, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (_, subtrees) = self.stack.last_mut()?;
+            match subtrees.next() {
+                None => {
+                    if let Some((true, _)) = self.stack.pop() {
+                        self.path.pop();
+                    }
+                }
+                Some(ErrorTree::Leaf(error)) => return Some((self.path.clone(), error)),
+                Some(ErrorTree::Edge(label, tree)) => {
+                    self.path.push(label);
+                    self.stack
+                        .push((true, std::slice::from_ref(tree.as_ref()).iter()));
+                }
+                Some(ErrorTree::Vec(trees)) => {
+                    self.stack.push((false, trees.iter()));
+                }
+            }
+        }
+    }
+}
+
+/// Iterates mutably over the errors of an `ErrorTree`, built by `ErrorTree::leaves_mut`.
+struct LeavesMut<'a, L, E> {
+    /// The subtrees not visited yet.
+    stack: Vec<std::slice::IterMut<'a, ErrorTree<L, E>>>,
+}
+
+impl<'a, L, E> Iterator for LeavesMut<'a, L, E> {
+    type Item = &'a mut E;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                None => {
+                    self.stack.pop();
+                }
+                Some(ErrorTree::Leaf(error)) => return Some(error),
+                Some(ErrorTree::Edge(_, tree)) => {
+                    self.stack
+                        .push(std::slice::from_mut(tree.as_mut()).iter_mut());
+                }
+                Some(ErrorTree::Vec(trees)) => {
+                    self.stack.push(trees.iter_mut());
+                }
+            }
+        }
+    }
+}
+
+/// Adds a label to the error tree.
+pub trait IntoErrorTree<L, E> {
+    /**
+    Adds a `label` to an error tree.
+    ```rust
+    # use error_trees::*;
+    struct Error(String);
+    let leaf = ErrorTree::leaf(Error("a regular error".into()));
+    let labeled_leaf = leaf.with_label("the label");
+    ```
+    */
+    fn with_label(self, label: L) -> ErrorTree<L, E>;
+}
+
+impl<L, E> IntoErrorTree<L, E> for E
+where
+    E: Into<ErrorTree<L, E>>,
+{
+    fn with_label(self, label: L) -> ErrorTree<L, E> {
+        ErrorTree::Edge(label, Box::new(self.into()))
+    }
+}
+
+impl<L, E> IntoErrorTree<L, E> for ErrorTree<L, E> {
+    fn with_label(self, label: L) -> ErrorTree<L, E> {
+        ErrorTree::Edge(label, Box::new(self))
+    }
+}
+
+impl<L, E> From<Vec<ErrorTree<L, E>>> for ErrorTree<L, E> {
+    fn from(subtrees: Vec<ErrorTree<L, E>>) -> Self {
+        ErrorTree::Vec(subtrees)
+    }
+}
+
+impl<L, E> From<Vec<E>> for ErrorTree<L, E>
+where
+    E: IntoErrorTree<L, E>,
+    ErrorTree<L, E>: From<E>,
+{
+    fn from(errors: Vec<E>) -> Self {
+        ErrorTree::Vec(errors.into_iter().map(|x| x.into()).collect_vec())
+    }
+}
+
+/// Convenience trait to convert tuple of `(success: T, errors: Vec<E>)` to a `result : Result<T, ErrorTree<L, E>>`
+pub trait IntoResult<T, E> {
+    /**
+    Turns `self` into a `Result`.
+
+    For tuples of `(success: T, errors: Vec<E>)`:
+    - It checks if `errors` is empty.
+        - If true, it will return `Ok(success)`.
+        - Otherwise it will return `Err(errors)`.
+
+    ```rust
+    # use itertools::*;
+    # use error_trees::*;
+    struct Error(String);
+    impl<L> From<Error> for ErrorTree<L, Error> {
+        fn from(e: Error) -> Self {
+            Self::leaf(e)
+        }
+    }
+
+    let result1: Result<(), _> = Err(Error("first".into())).label_error("one");
+    let result2: Result<(), _> = Err(Error("second".into())).label_error("two");
+
+    let final_result: Result<Vec<_>, ErrorTree<_, _>> = vec![result1, result2]
+        .into_iter()
+        .partition_result()
+        .into_result();
+    ```
+
+    For `errors: Vec<E>`:
+    - It checks if `errors` is empty.
+    - If true, it will return `Ok(())`.
+    - Otherwise, it will return `Err(errors)`.
+
+    Since the trait is implemented for tuples of `(success: T, errors: Vec<E>)`
+    and for `Vec<E>`, it works well with `partition_result` from the `itertools` crate!
+
+    ```rust
+    # use itertools::*;
+    # use error_trees::*;
+    struct Error(String);
+
+    let error1 = ErrorTree::leaf(Error("first".into())).with_label("one");
+    let error2 = ErrorTree::leaf(Error("second".into())).with_label("two");
+
+    let final_result: Result<_, ErrorTree<_, _>> = vec![error1, error2]
+        .into_result();
+    ```
+    */
+    fn into_result(self) -> Result<T, E>;
+}
+
+impl<T, IE, E> IntoResult<T, E> for (T, Vec<IE>)
+where
+    Vec<IE>: Into<E>,
+{
+    fn into_result(self) -> Result<T, E> {
+        let (oks, errs) = self;
+        if errs.is_empty() {
+            Ok(oks)
+        } else {
+            Err(errs.into())
+        }
+    }
+}
+
+impl<IE, E> IntoResult<(), E> for Vec<IE>
+where
+    Vec<IE>: Into<E>,
+{
+    fn into_result(self) -> Result<(), E> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into())
+        }
+    }
+}
+
+/// Convenience trait to turn a collection of errors into a `Result`, also validating the success.
+pub trait IntoResultIf<T, E> {
+    /**
+    Like `into_result`, but the success is also validated by `is_ok`.
+
+    It returns `Ok(success)` only if there are no errors and `is_ok(&success)` is true.
+    If there are no errors, but `is_ok` fails, it returns the error built by `rejected`.
+
+    ```rust
+    # use itertools::*;
+    # use error_trees::*;
+    struct Error(String);
+
+    let successes: Vec<u32> = vec![];
+    let errors: Vec<ErrorTree<&'static str, Error>> = vec![];
+
+    let result: Result<_, ErrorTree<_, _>> = (successes, errors).into_result_if(
+        |successes| !successes.is_empty(),
+        || ErrorTree::leaf(Error("nothing succeeded".into())),
+    );
+
+    assert_eq!(result.unwrap_err().count_leaves(), 1);
+    ```
+    */
+    fn into_result_if<F: FnOnce(&T) -> bool, R: FnOnce() -> E>(
+        self,
+        is_ok: F,
+        rejected: R,
+    ) -> Result<T, E>;
+}
+
+impl<T, IE, E> IntoResultIf<T, E> for (T, Vec<IE>)
+where
+    Vec<IE>: Into<E>,
+{
+    fn into_result_if<F: FnOnce(&T) -> bool, R: FnOnce() -> E>(
+        self,
+        is_ok: F,
+        rejected: R,
+    ) -> Result<T, E> {
+        let (oks, errs) = self;
+        if !errs.is_empty() {
+            Err(errs.into())
+        } else if is_ok(&oks) {
+            Ok(oks)
+        } else {
+            Err(rejected())
+        }
+    }
+}
+
+impl<IE, E> IntoResultIf<(), E> for Vec<IE>
+where
+    Vec<IE>: Into<E>,
+{
+    fn into_result_if<F: FnOnce(&()) -> bool, R: FnOnce() -> E>(
+        self,
+        is_ok: F,
+        rejected: R,
+    ) -> Result<(), E> {
+        if !self.is_empty() {
+            Err(self.into())
+        } else if is_ok(&()) {
+            Ok(())
+        } else {
+            Err(rejected())
+        }
+    }
+}
+
+/**
+Runs every step, even after a failure, and collects the errors of the failed steps.
+
+Each error is labeled with the label of its step.
+
+```rust
+# use error_trees::*;
+#[derive(Debug)]
+struct Error(String);
+
+let steps: Vec<(_, Step<Error>)> = vec![
+    ("first", Box::new(|| Ok(()))),
+    ("second", Box::new(|| Err(Error("failed".into())))),
+];
+
+let flat_errors = accumulate(steps).unwrap_err().flatten_tree();
+
+assert_eq!(flat_errors.len(), 1);
+assert_eq!(flat_errors[0].path, vec!["second"]);
+```
+*/
+pub fn accumulate<L, E>(steps: Vec<(L, Step<E>)>) -> Result<(), ErrorTree<L, E>> {
+    steps
+        .into_iter()
+        .filter_map(|(label, step)| step().err().map(|e| ErrorTree::leaf(e).with_label(label)))
+        .collect_vec()
+        .into_result()
+}
+
+/**
+Runs `f`, labeling its error with `label`.
+
+```rust
+# use error_trees::*;
+#[derive(Debug)]
+struct Error(String);
+
+fn parse() -> Result<u32, Error> {
+    Err(Error("invalid".into()))
+}
+
+fn load() -> Result<u32, ErrorTree<&'static str, Error>> {
+    let config = labeled("parse config", || parse())?;
+    Ok(config)
+}
+
+let flat_errors = load().unwrap_err().flatten_tree();
+assert_eq!(flat_errors[0].path, vec!["parse config"]);
+```
+*/
+pub fn labeled<T, E, L, F: FnOnce() -> Result<T, E>>(label: L, f: F) -> Result<T, ErrorTree<L, E>> {
+    f().map_err(|e| ErrorTree::leaf(e).with_label(label))
+}
+
+/**
+Collects the successes in their original positions, with `None` where an error occurred,
+and the errors in a tree, labeled by their positions.
+
+The tree is `None` if there are no errors.
+
+```rust
+# use error_trees::*;
+let results = vec![Ok(1), Err("error"), Ok(3)];
+
+let (successes, errors) = collect_positional(results);
+
+assert_eq!(successes, vec![Some(1), None, Some(3)]);
+assert_eq!(errors.unwrap().flatten_tree()[0].path, vec![1]);
+```
+*/
+pub fn collect_positional<T, E, I>(results: I) -> (Vec<Option<T>>, Option<ErrorTree<usize, E>>)
+where
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    let mut successes = Vec::new();
+    let mut errors = Vec::new();
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(success) => successes.push(Some(success)),
+            Err(error) => {
+                successes.push(None);
+                errors.push(ErrorTree::leaf(error).with_label(index));
+            }
+        }
+    }
+    (successes, (!errors.is_empty()).then(|| errors.into()))
+}
+
+/**
+Creates a `Leaf` tree of a string error, for prototypes that don't need their own error type.
+
+```rust
+# use error_trees::*;
+let tree = string_tree(
+    "config",
+    vec![
+        string_leaf("missing name"),
+        string_tree("port", vec![string_leaf("not a number")]),
+    ],
+);
+
+let flat_errors = tree.flatten_tree();
+
+assert_eq!(flat_errors[0].path, vec!["config"]);
+assert_eq!(flat_errors[0].error, "missing name");
+assert_eq!(flat_errors[1].path, vec!["port", "config"]);
+assert_eq!(flat_errors[1].error, "not a number");
+```
+*/
+pub fn string_leaf(error: impl Into<String>) -> ErrorTree<String, String> {
+    ErrorTree::leaf(error.into())
+}
+
+/// Creates a tree of string errors, grouping the `children` under a string `label`.
+///
+/// See `string_leaf`.
+pub fn string_tree(
+    label: impl Into<String>,
+    children: Vec<ErrorTree<String, String>>,
+) -> ErrorTree<String, String> {
+    ErrorTree::Vec(children).with_label(label.into())
+}
+
+/// A fallible step, to be run by `accumulate`.
+pub type Step<E> = Box<dyn FnOnce() -> Result<(), E>>;
+
+/// Convenience trait to label errors within a `Result`.
+pub trait LabelResult<T, L, E> {
+    /**
+    Maps a label to the `ErrorTree` within the result.
+
+    ```rust
+    # use itertools::*;
+    # use error_trees::*;
+    struct Error(String);
+    let result: Result<(), ErrorTree<&'static str, Error>> = Ok(());
+    let labeled_result = result.label_error("the label");
+    ```
+    */
+    fn label_error(self, label: L) -> Result<T, ErrorTree<L, E>>;
+}
+
+impl<T, L, E> LabelResult<T, L, E> for Result<T, E>
+where
+    ErrorTree<L, E>: From<E>,
+{
+    fn label_error(self, label: L) -> Result<T, ErrorTree<L, E>> {
+        self.map_err(|e| {
+            let tree: ErrorTree<L, E> = e.into();
+            tree.with_label(label)
+        })
+    }
+}
+
+impl<T, L, E> LabelResult<T, L, E> for Result<T, ErrorTree<L, E>> {
+    fn label_error(self, label: L) -> Result<T, ErrorTree<L, E>> {
+        self.map_err(|tree| tree.with_label(label))
+    }
+}
+
+/// Convenience trait to label errors within a `Result`, without repeating the outermost label.
+pub trait LabelResultOnce<T, L, E> {
+    /**
+    Maps a label to the `ErrorTree` within the result, unless its outermost label is already `label`.
+
+    This avoids paths like `["x", "x"]` when a function labels the result of a helper
+    that already labeled it.
+
+    ```rust
+    # use error_trees::*;
+    let result: Result<(), ErrorTree<&'static str, &'static str>> =
+        Err(ErrorTree::leaf("error").with_label("x"));
+    let result = result.label_error_once("x");
+
+    assert_eq!(result.unwrap_err().flatten_tree()[0].path, vec!["x"]);
+    ```
+    */
+    fn label_error_once(self, label: L) -> Result<T, ErrorTree<L, E>>;
+}
+
+fn with_label_once<L: PartialEq, E>(tree: ErrorTree<L, E>, label: L) -> ErrorTree<L, E> {
+    match &tree {
+        ErrorTree::Edge(outer, _) if *outer == label => tree,
+        _ => tree.with_label(label),
+    }
+}
+
+impl<T, L, E> LabelResultOnce<T, L, E> for Result<T, E>
+where
+    L: PartialEq,
+    ErrorTree<L, E>: From<E>,
+{
+    fn label_error_once(self, label: L) -> Result<T, ErrorTree<L, E>> {
+        self.map_err(|e| with_label_once(e.into(), label))
+    }
+}
+
+impl<T, L, E> LabelResultOnce<T, L, E> for Result<T, ErrorTree<L, E>>
+where
+    L: PartialEq,
+{
+    fn label_error_once(self, label: L) -> Result<T, ErrorTree<L, E>> {
+        self.map_err(|tree| with_label_once(tree, label))
+    }
+}
+
+/// Convenience trait to turn the results of a phase into a `Result`, keeping the errors of the previous phases.
+pub trait IntoResultMerging<T, L, E> {
+    /**
+    Like `into_result`, but the errors are merged with the `existing` errors of the previous phases
+    in a `Vec` node.
+
+    It returns `Ok(success)` only if there are no errors, neither in `self` nor in `existing`.
+
+    ```rust
+    # use error_trees::*;
+    let phase1: (u32, Vec<ErrorTree<&'static str, &'static str>>) =
+        (1, vec![ErrorTree::leaf("error1").with_label("parse")]);
+    let existing = phase1.into_result().err();
+
+    let phase2: (u32, Vec<ErrorTree<&'static str, &'static str>>) =
+        (2, vec![ErrorTree::leaf("error2").with_label("check")]);
+    let result = phase2.into_result_merging(existing);
+
+    assert_eq!(result.unwrap_err().flatten_tree().len(), 2);
+    ```
+    */
+    fn into_result_merging(self, existing: Option<ErrorTree<L, E>>) -> Result<T, ErrorTree<L, E>>;
+}
+
+impl<T, IE, L, E> IntoResultMerging<T, L, E> for (T, Vec<IE>)
+where
+    Vec<IE>: Into<ErrorTree<L, E>>,
+{
+    fn into_result_merging(self, existing: Option<ErrorTree<L, E>>) -> Result<T, ErrorTree<L, E>> {
+        let (oks, errs) = self;
+        let existing = existing.filter(|tree| tree.count_leaves() > 0);
+        match (existing, errs.is_empty()) {
+            (None, true) => Ok(oks),
+            (Some(existing), true) => Err(existing),
+            (None, false) => Err(errs.into()),
+            (Some(existing), false) => Err(ErrorTree::Vec(vec![existing, errs.into()])),
+        }
+    }
+}
+
+/// Convenience trait to convert a `Result` with a `Vec` of errors into a `Result` with an `ErrorTree`.
+pub trait LiftVecError<T, L, E> {
+    /**
+    Wraps the errors in a `Vec` node of leaves.
+
+    ```rust
+    # use error_trees::*;
+    #[derive(Debug)]
+    struct Error(String);
+
+    let result: Result<(), Vec<Error>> = Err(vec![Error("first".into()), Error("second".into())]);
+    let result: Result<(), ErrorTree<&'static str, Error>> = result.lift_vec_error();
+
+    assert_eq!(result.unwrap_err().flatten_tree().len(), 2);
+    ```
+    */
+    fn lift_vec_error(self) -> Result<T, ErrorTree<L, E>>;
+}
+
+impl<T, L, E> LiftVecError<T, L, E> for Result<T, Vec<E>> {
+    fn lift_vec_error(self) -> Result<T, ErrorTree<L, E>> {
+        self.map_err(|errors| ErrorTree::Vec(errors.into_iter().map(ErrorTree::leaf).collect_vec()))
+    }
+}
+
+/// Convenience trait to collect an iterator of `Result`s, keeping all the errors.
+pub trait CollectTree<T, L, E> {
+    /**
+    Collects the successes in a `Vec`, or all the errors in an `ErrorTree`.
+
+    Unlike `collect::<Result<Vec<T>, _>>()` from the standard library, which stops at the first error,
+    this goes through the whole iterator and gathers every error in a `Vec` node.
+    The standard `collect` can't be changed for `ErrorTree`, since the standard library already
+    implements `FromIterator` for every `Result`.
+
+    ```rust
+    # use error_trees::*;
+    #[derive(Debug)]
+    struct Error(String);
+
+    let results: Vec<Result<u32, ErrorTree<&'static str, Error>>> = vec![
+        Ok(1),
+        Err(ErrorTree::leaf(Error("first".into())).with_label("one")),
+        Err(ErrorTree::leaf(Error("second".into())).with_label("two")),
+    ];
+
+    let flat_errors = results.into_iter().collect_tree().unwrap_err().flatten_tree();
+
+    assert_eq!(flat_errors.len(), 2);
+    ```
+    */
+    fn collect_tree(self) -> Result<Vec<T>, ErrorTree<L, E>>;
+}
+
+impl<T, L, E, I> CollectTree<T, L, E> for I
+where
+    I: Iterator<Item = Result<T, ErrorTree<L, E>>>,
+{
+    fn collect_tree(self) -> Result<Vec<T>, ErrorTree<L, E>> {
+        self.partition_result::<Vec<_>, Vec<_>, _, _>()
+            .into_result()
+    }
+}
+
+/// Convenience trait to label the errors of an iterator of `(item, result)` pairs by their items.
+pub trait LabelWith<T, U, L, E> {
+    /**
+    Labels the error of each result with a label computed from its item by `f`,
+    dropping the items.
+
+    ```rust
+    # use error_trees::*;
+    struct Job {
+        id: u32,
+    }
+
+    let results = vec![(Job { id: 1 }, Ok(10)), (Job { id: 2 }, Err("failed"))];
+
+    let tree = results
+        .into_iter()
+        .label_with(|job: &Job| job.id)
+        .collect_tree()
+        .unwrap_err();
+
+    assert_eq!(tree.flatten_tree()[0].path, vec![2]);
+    ```
+    */
+    fn label_with<F: FnMut(&T) -> L>(self, f: F) -> LabeledWith<Self, F>
+    where
+        Self: Sized;
+}
+
+impl<T, U, L, E, I> LabelWith<T, U, L, E> for I
+where
+    I: Iterator<Item = (T, Result<U, E>)>,
+{
+    fn label_with<F: FnMut(&T) -> L>(self, f: F) -> LabeledWith<Self, F> {
+        LabeledWith { iter: self, f }
+    }
+}
+
+/// An iterator that labels the errors of `(item, result)` pairs by their items.
+///
+/// Built by `LabelWith::label_with`.
+pub struct LabeledWith<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<T, U, L, E, I, F> Iterator for LabeledWith<I, F>
+where
+    I: Iterator<Item = (T, Result<U, E>)>,
+    F: FnMut(&T) -> L,
+{
+    type Item = Result<U, ErrorTree<L, E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (item, result) = self.iter.next()?;
+        Some(result.map_err(|error| ErrorTree::leaf(error).with_label((self.f)(&item))))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/**
+Labels the errors within a `Result` with a formatted `String` label.
+
+`label_error_fmt!(result, "item[{}]", i)` is the same as `result.label_error(format!("item[{}]", i))`.
+
+```rust
+# use error_trees::*;
+#[derive(Debug)]
+struct Error(String);
+impl<L> From<Error> for ErrorTree<L, Error> {
+    fn from(e: Error) -> Self {
+        Self::leaf(e)
+    }
+}
+
+let index = 3;
+let result: Result<(), _> = label_error_fmt!(Err(Error("error".into())), "item[{}]", index);
+
+let flat_errors = result.flatten_results().unwrap_err();
+assert_eq!(flat_errors[0].path, vec!["item[3]".to_string()]);
+```
+*/
+#[macro_export]
+macro_rules! label_error_fmt {
+    ($result:expr, $($arg:tt)*) => {
+        $crate::LabelResult::label_error($result, ::std::format!($($arg)*))
+    };
+}
+
+/**
+Labels the error of a `Result` and returns it early, like the `?` operator.
+
+`try_tree!(result, label)` is the same as `result.label_error(label)?`, so it can be used
+in functions returning `Result<_, ErrorTree<L, E>>`, or any error type that can be built
+from an `ErrorTree<L, E>`. The error is returned at once: to keep going after an error,
+collect the labeled results instead, for example with `collect_tree`.
+
+```rust
+# use error_trees::*;
+#[derive(Debug)]
+struct Error(String);
+impl<L> From<Error> for ErrorTree<L, Error> {
+    fn from(e: Error) -> Self {
+        Self::leaf(e)
+    }
+}
+
+fn parse(input: &str) -> Result<u32, Error> {
+    input.parse().map_err(|_| Error(format!("invalid number {}", input)))
+}
+
+fn parse_pair(a: &str, b: &str) -> Result<(u32, u32), ErrorTree<&'static str, Error>> {
+    let a = try_tree!(parse(a), "first");
+    let b = try_tree!(parse(b), "second");
+    Ok((a, b))
+}
+
+let flat_errors = parse_pair("1", "x").flatten_results().unwrap_err();
+assert_eq!(flat_errors[0].path, vec!["second"]);
+```
+*/
+#[macro_export]
+macro_rules! try_tree {
+    ($result:expr, $label:expr) => {
+        $crate::LabelResult::label_error($result, $label)?
+    };
+}
+
+pub trait FlattenResultErrors<T, L, E> {
+    fn flatten_results(self) -> Result<T, Vec<FlatError<L, E>>>;
+}
+
+impl<T, L, E> FlattenResultErrors<T, L, E> for Result<T, ErrorTree<L, E>>
+where
+    L: Clone,
+{
+    fn flatten_results(self) -> Result<T, Vec<FlatError<L, E>>> {
+        self.map_err(|tree| tree.flatten_tree())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    fn faulty(error: &str) -> Result<(), Error> {
+        Err(Error(error.into()))
+    }
+
+    #[test]
+    fn can_build_tree_from_vec_of_results() {
+        let result_1 = faulty("error1").map_err(|e| e.with_label("label1"));
+        let result_2 = faulty("error2").map_err(|e| e.with_label("label2"));
+
+        let (_, errors): (Vec<_>, Vec<_>) = vec![result_1, result_2].into_iter().partition_result();
+
+        let tree: ErrorTree<&'static str, Error> = errors.into();
+        let tree = tree.with_label("parent_label");
+
+        let flat_errors = tree.flatten_tree();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError {
+                        path: path1,
+                        error: Error(error1),
+                    },
+                    FlatError {
+                        path: path2,
+                        error: Error(error2),
+                    },
+                ]
+                if path1 == &vec!["label1", "parent_label"]
+                && path2 == &vec!["label2", "parent_label"]
+                && error1 == "error1"
+                && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn can_call_into_result_from_vec_of_results() {
+        let result_1 = faulty("error1").map_err(|e| e.with_label("label1"));
+        let result_2 = faulty("error2").map_err(|e| e.with_label("label2"));
+
+        let result: Result<Vec<()>, ErrorTree<_, _>> = vec![result_1, result_2]
+            .into_iter()
+            .partition_result()
+            .into_result();
+
+        let flat_result = result.map_err(|e| e.flatten_tree());
+
+        let flat_errors = flat_result.unwrap_err();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError {
+                        path: path1,
+                        error: Error(error1),
+                    },
+                    FlatError {
+                        path: path2,
+                        error: Error(error2),
+                    },
+                ]
+                if path1 == &vec!["label1"]
+                && path2 == &vec!["label2"]
+                && error1 == "error1"
+                && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn can_call_into_result_from_vec_of_errors() {
+        let error1 = Error("error1".into()).with_label("label1");
+        let error2 = Error("error2".into()).with_label("label2");
+
+        let result: Result<_, ErrorTree<_, _>> = vec![error1, error2].into_result();
+
+        let flat_result = result.map_err(|e| e.flatten_tree());
+
+        let flat_errors = flat_result.unwrap_err();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError {
+                        path: path1,
+                        error: Error(error1),
+                    },
+                    FlatError {
+                        path: path2,
+                        error: Error(error2),
+                    },
+                ]
+                if path1 == &vec!["label1"]
+                && path2 == &vec!["label2"]
+                && error1 == "error1"
+                && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn take_errors_stops_early() {
+        use std::{cell::Cell, rc::Rc};
+
+        struct CountedLabel(Rc<Cell<usize>>);
+
+        impl Clone for CountedLabel {
+            fn clone(&self) -> Self {
+                self.0.set(self.0.get() + 1);
+                Self(self.0.clone())
+            }
+        }
+
+        let clones = Rc::new(Cell::new(0));
+        let errors = (0..1000)
+            .map(|i| ErrorTree::leaf(Error(format!("error{}", i))))
+            .collect_vec();
+        let tree = ErrorTree::from(errors).with_label(CountedLabel(clones.clone()));
+
+        let flat_errors = tree.take_errors(3);
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError { error: Error(error1), .. },
+                    FlatError { error: Error(error2), .. },
+                    FlatError { error: Error(error3), .. },
+                ]
+                if error1 == "error0"
+                && error2 == "error1"
+                && error3 == "error2"
+            ),
+            "unexpected: {:#?}",
+            flat_errors.iter().map(|e| &e.error).collect_vec()
+        );
+        assert_eq!(clones.get(), 3);
+    }
+
+    #[test]
+    fn into_result_if_fails_when_check_fails() {
+        let results: Vec<Result<u32, ErrorTree<&'static str, Error>>> = vec![Ok(1), Ok(2)];
+
+        let (successes, errors): (Vec<_>, Vec<_>) = results.into_iter().partition_result();
+        let result: Result<_, ErrorTree<_, _>> = (successes, errors).into_result_if(
+            |successes| successes.iter().sum::<u32>() > 3,
+            || Error("sum too small".into()).with_label("sum"),
+        );
+
+        assert_eq!(
+            result
+                .unwrap_err()
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![(vec!["sum"], Error("sum too small".into()))]
+        );
+    }
+
+    #[test]
+    fn into_result_if_keeps_the_errors_without_checking() {
+        let results: Vec<Result<u32, ErrorTree<&'static str, Error>>> =
+            vec![Ok(1), Err(Error("error".into()).into())];
+
+        let (successes, errors): (Vec<_>, Vec<_>) = results.into_iter().partition_result();
+        let result: Result<_, ErrorTree<_, _>> = (successes, errors).into_result_if(
+            |_| unreachable!("the check runs only without errors"),
+            || Error("rejected".into()).into(),
+        );
+
+        assert_eq!(
+            result.unwrap_err().cloned_errors(),
+            vec![Error("error".into())]
+        );
+    }
+
+    #[test]
+    fn into_result_if_succeeds_when_check_succeeds() {
+        let results: Vec<Result<u32, ErrorTree<&'static str, Error>>> = vec![Ok(1), Ok(2)];
+
+        let (successes, errors): (Vec<_>, Vec<_>) = results.into_iter().partition_result();
+        let result: Result<_, ErrorTree<_, _>> = (successes, errors).into_result_if(
+            |successes| successes.iter().sum::<u32>() == 3,
+            || Error("sum too small".into()).into(),
+        );
+
+        assert!(matches!(&result, Ok(successes) if successes == &vec![1, 2]));
+    }
+
+    #[test]
+    fn to_markdown_renders_nested_bullets() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            vec![
+                ErrorTree::leaf("error1").with_label("label1"),
+                ErrorTree::leaf("error2").with_label("label2"),
+            ]
+            .into(),
+            ErrorTree::leaf("error3"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert_eq!(
+            tree.to_markdown(),
+            "- parent_label\n  - label1\n    - error1\n  - label2\n    - error2\n  - error3\n"
+        );
+    }
+
+    #[test]
+    fn assign_ids_are_unique_and_stable() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let ids = tree.assign_ids();
+
+        assert_eq!(
+            ids.iter().map(|(id, _)| *id).collect_vec(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert!(
+            matches!(
+                &ids[..],
+                [
+                    (_, ErrorTree::Edge("parent_label", _)),
+                    (_, ErrorTree::Vec(_)),
+                    (_, ErrorTree::Edge("label1", _)),
+                    (_, ErrorTree::Leaf(Error(error1))),
+                    (_, ErrorTree::Leaf(Error(error2))),
+                ]
+                if error1 == "error1" && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            ids
+        );
+
+        let ids_again = tree.assign_ids();
+        assert!(ids
+            .iter()
+            .zip(&ids_again)
+            .all(|((id1, node1), (id2, node2))| id1 == id2 && std::ptr::eq(*node1, *node2)));
+    }
+
+    #[test]
+    fn to_json_is_valid_json() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error \"1\"\n").with_label("label\\1"),
+            ErrorTree::leaf("error\u{1}2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let json: serde_json::Value = serde_json::from_str(&tree.to_json()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "label": "parent_label",
+                "children": [{
+                    "children": [
+                        { "label": "label\\1", "children": [{ "error": "error \"1\"\n" }] },
+                        { "error": "error\u{1}2" },
+                    ]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn accumulate_continues_past_failures() {
+        let steps: Vec<(_, Step<Error>)> = vec![
+            ("step1", Box::new(|| faulty("error1"))),
+            ("step2", Box::new(|| Ok(()))),
+            ("step3", Box::new(|| faulty("error3"))),
+        ];
+
+        let flat_errors = accumulate(steps).unwrap_err().flatten_tree();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError {
+                        path: path1,
+                        error: Error(error1),
+                    },
+                    FlatError {
+                        path: path3,
+                        error: Error(error3),
+                    },
+                ]
+                if path1 == &vec!["step1"]
+                && path3 == &vec!["step3"]
+                && error1 == "error1"
+                && error3 == "error3"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn strip_outer_label_is_the_inverse_of_with_label() {
+        let tree = || -> ErrorTree<&'static str, Error> {
+            vec![
+                Error("error1".into()).with_label("label1"),
+                Error("error2".into()).with_label("label2"),
+            ]
+            .into()
+        };
+
+        let flat_errors = tree().flatten_tree();
+        let stripped_flat_errors = tree().with_label("x").strip_outer_label().flatten_tree();
+
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            stripped_flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn strip_outer_label_keeps_unlabeled_trees() {
+        let tree: ErrorTree<&'static str, Error> = ErrorTree::leaf(Error("error".into()));
+
+        assert!(matches!(
+            &tree.strip_outer_label(),
+            ErrorTree::Leaf(Error(error)) if error == "error"
+        ));
+    }
+
+    #[test]
+    fn flatten_tree_handles_deep_trees() {
+        let mut tree: ErrorTree<usize, Error> = ErrorTree::leaf(Error("error".into()));
+        for depth in 0..100_000 {
+            tree = tree.with_label(depth);
+        }
+
+        let flat_errors = tree.flatten_tree();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [FlatError { path, error: Error(error) }]
+                if path.len() == 100_000
+                && path[0] == 0
+                && path[99_999] == 99_999
+                && error == "error"
+            ),
+            "unexpected: {:#?}",
+            flat_errors.iter().map(|e| &e.error).collect_vec()
+        );
+    }
+
+    #[test]
+    fn drop_handles_deep_trees() {
+        let mut tree: ErrorTree<usize, Error> = ErrorTree::leaf(Error("error".into()));
+        for depth in 0..1_000_000 {
+            tree = vec![tree.with_label(depth)].into();
+        }
+
+        drop(tree);
+    }
+
+    #[test]
+    fn labeled_keeps_successes() {
+        let result = labeled("label", || Ok::<_, Error>(42));
+
+        assert!(matches!(result, Ok(42)));
+    }
+
+    #[test]
+    fn labeled_labels_errors() {
+        let result = labeled("label", || faulty("error"));
+
+        let flat_errors = result.flatten_results().unwrap_err();
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [FlatError { path, error: Error(error) }]
+                if path == &vec!["label"] && error == "error"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn remove_labels_keeps_errors() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into())
+                .with_label("internal")
+                .with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree = tree.with_label("internal");
+
+        let flat_errors = tree
+            .remove_labels(|label| *label == "internal")
+            .flatten_tree();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError {
+                        path: path1,
+                        error: Error(error1),
+                    },
+                    FlatError {
+                        path: path2,
+                        error: Error(error2),
+                    },
+                ]
+                if path1 == &vec!["label1"]
+                && path2 == &vec!["label2"]
+                && error1 == "error1"
+                && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn collect_tree_gathers_all_errors() {
+        let results = vec![
+            faulty("error1").label_error("label1"),
+            Ok(()),
+            faulty("error2").label_error("label2"),
+        ];
+
+        let result: Result<Vec<()>, ErrorTree<&'static str, Error>> =
+            results.into_iter().collect_tree();
+
+        let flat_errors = result.flatten_results().unwrap_err();
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError {
+                        path: path1,
+                        error: Error(error1),
+                    },
+                    FlatError {
+                        path: path2,
+                        error: Error(error2),
+                    },
+                ]
+                if path1 == &vec!["label1"]
+                && path2 == &vec!["label2"]
+                && error1 == "error1"
+                && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn collect_tree_keeps_successes() {
+        let results: Vec<Result<u32, ErrorTree<&'static str, Error>>> = vec![Ok(1), Ok(2)];
+
+        let result = results.into_iter().collect_tree();
+
+        assert!(matches!(&result, Ok(successes) if successes == &vec![1, 2]));
+    }
+
+    #[test]
+    fn leaf_here_captures_the_location() {
+        let tree = ErrorTree::leaf_here(Error("error".into()));
+        let line = line!() - 1;
+
+        let flat_errors = tree.flatten_tree();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [FlatError { path, error: Error(error) }]
+                if path.len() == 1
+                && path[0].file() == file!()
+                && path[0].line() == line
+                && error == "error"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn unflatten_is_the_inverse_of_flatten_tree() {
+        let tree = || -> ErrorTree<&'static str, Error> {
+            let group: ErrorTree<_, _> = vec![
+                Error("error1".into()).with_label("label1"),
+                Error("error2".into()).with_label("label1"),
+                ErrorTree::leaf(Error("error3".into())),
+            ]
+            .into();
+            vec![
+                group.with_label("parent_label"),
+                Error("error4".into()).with_label("label2"),
+                Error("error5".into()).with_label("label1"),
+            ]
+            .into()
+        };
+
+        let unflattened = ErrorTree::unflatten(tree().flatten_tree());
+
+        assert!(
+            matches!(&unflattened, ErrorTree::Vec(trees) if trees.len() == 3),
+            "unexpected: {:#?}",
+            unflattened
+        );
+        assert_eq!(
+            unflattened
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            tree()
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn render_truncated_stops_after_max_lines() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2").with_label("label2"),
+            ErrorTree::leaf("error3").with_label("label3"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let rendered = tree.render_truncated(4);
+
+        assert_eq!(
+            rendered,
+            "parent_label\n  label1\n    error1\n  label2\n... (3 more)\n"
+        );
+        assert_eq!(tree.render_truncated(7).lines().count(), 7);
+    }
+
+    #[test]
+    fn static_labels_are_not_allocated_when_flattening() {
+        let tree: ErrorTree<CowLabel, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())).with_static_label("label1"),
+            ErrorTree::leaf(Error("error2".into())).with_label(Cow::Owned("label2".into())),
+        ]
+        .into();
+        let tree = tree.with_static_label("parent_label");
+
+        let flat_errors = tree.flatten_tree();
+
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [
+                    FlatError {
+                        path: path1,
+                        ..
+                    },
+                    FlatError {
+                        path: path2,
+                        ..
+                    },
+                ]
+                if matches!(&path1[..], [Cow::Borrowed("label1"), Cow::Borrowed("parent_label")])
+                && matches!(&path2[..], [Cow::Owned(label2), Cow::Borrowed("parent_label")] if label2 == "label2")
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn iter_paths_borrows_labels() {
+        #[derive(Debug, PartialEq)]
+        struct Label(&'static str);
+
+        let tree: ErrorTree<Label, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())).with_label(Label("label1")),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let tree = tree.with_label(Label("parent_label"));
+
+        let paths = tree.iter_paths().collect_vec();
+
+        assert!(
+            matches!(
+                &paths[..],
+                [
+                    (path1, Error(error1)),
+                    (path2, Error(error2)),
+                ]
+                if path1 == &vec![&Label("parent_label"), &Label("label1")]
+                && path2 == &vec![&Label("parent_label")]
+                && error1 == "error1"
+                && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            paths
+        );
+    }
+
+    #[test]
+    fn reduce_vecs_visits_groups_in_pre_order() {
+        let inner: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            inner.with_label("inner"),
+            ErrorTree::leaf(Error("error3".into())),
+            ErrorTree::Vec(vec![]),
+        ]
+        .into();
+
+        let leaf_counts = tree.reduce_vecs(|group| {
+            group
+                .iter()
+                .map(|tree| tree.iter_paths().count())
+                .sum::<usize>()
+        });
+
+        assert_eq!(leaf_counts, vec![3, 2, 0]);
+    }
+
+    #[test]
+    fn has_repeated_label_on_path_finds_repeated_labels() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into())
+                .with_label("parent_label")
+                .with_label("label2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert!(tree.has_repeated_label_on_path());
+    }
+
+    #[test]
+    fn has_repeated_label_on_path_ignores_labels_on_different_paths() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label"),
+            Error("error2".into()).with_label("label"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert!(!tree.has_repeated_label_on_path());
+    }
+
+    #[test]
+    fn lift_vec_error_wraps_errors_in_leaves() {
+        let result: Result<(), Vec<Error>> = Err(vec![
+            Error("error1".into()),
+            Error("error2".into()),
+            Error("error3".into()),
+        ]);
+
+        let result: Result<(), ErrorTree<&'static str, Error>> =
+            result.lift_vec_error().label_error("legacy");
+
+        let flat_errors = result.flatten_results().unwrap_err();
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["legacy"], Error("error1".into())),
+                (vec!["legacy"], Error("error2".into())),
+                (vec!["legacy"], Error("error3".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn lift_vec_error_keeps_successes() {
+        let result: Result<u32, Vec<Error>> = Ok(42);
+
+        let result: Result<u32, ErrorTree<&'static str, Error>> = result.lift_vec_error();
+
+        assert!(matches!(result, Ok(42)));
+    }
+
+    #[test]
+    fn label_error_fmt_formats_the_label() {
+        let results = (0..2)
+            .map(|i| label_error_fmt!(faulty(&format!("error{}", i)), "item[{}]", i))
+            .collect_vec();
+
+        let result: Result<Vec<()>, ErrorTree<String, Error>> = results.into_iter().collect_tree();
+
+        let flat_errors = result.flatten_results().unwrap_err();
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["item[0]".to_string()], Error("error0".into())),
+                (vec!["item[1]".to_string()], Error("error1".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_at_depth_filters_by_depth() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            group.with_label("parent_label"),
+            Error("error3".into()).with_label("label3"),
+        ]
+        .into();
+
+        assert_eq!(tree.errors_at_depth(0), Vec::<&Error>::new());
+        assert_eq!(tree.errors_at_depth(1), vec![&Error("error3".into())]);
+        assert_eq!(
+            tree.errors_at_depth(2),
+            vec![&Error("error1".into()), &Error("error2".into())]
+        );
+    }
+
+    #[test]
+    fn flatten_enumerated_numbers_errors_from_zero() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> =
+            vec![group, ErrorTree::leaf(Error("error3".into()))].into();
+
+        let flat_errors = tree.flatten_enumerated();
+
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|(index, e)| (index, e.error))
+                .collect_vec(),
+            vec![
+                (0, Error("error1".into())),
+                (1, Error("error2".into())),
+                (2, Error("error3".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_sharing_root_shares_equal_labels() {
+        let tree1: ErrorTree<&'static str, Error> = Error("error1".into()).with_label("label");
+        let tree2: ErrorTree<&'static str, Error> = Error("error2".into()).with_label("label");
+
+        let merged = tree1.merge_sharing_root(tree2);
+
+        assert!(
+            matches!(
+                &merged,
+                ErrorTree::Edge("label", tree)
+                if matches!(
+                    &**tree,
+                    ErrorTree::Vec(trees)
+                    if matches!(&trees[..], [ErrorTree::Leaf(_), ErrorTree::Leaf(_)])
+                )
+            ),
+            "unexpected: {:#?}",
+            merged
+        );
+    }
+
+    #[test]
+    fn merge_sharing_root_keeps_distinct_labels() {
+        let tree1: ErrorTree<&'static str, Error> = Error("error1".into()).with_label("label1");
+        let tree2: ErrorTree<&'static str, Error> = Error("error2".into()).with_label("label2");
+
+        let merged = tree1.merge_sharing_root(tree2);
+
+        assert!(
+            matches!(
+                &merged,
+                ErrorTree::Vec(trees)
+                if matches!(&trees[..], [ErrorTree::Edge("label1", _), ErrorTree::Edge("label2", _)])
+            ),
+            "unexpected: {:#?}",
+            merged
+        );
+    }
+
+    #[test]
+    fn flatten_tree_sorted_by_label_sorts_siblings() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("d"),
+            Error("error2".into()).with_label("c"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error3".into()).with_label("b"),
+            group.with_label("a"),
+            ErrorTree::leaf(Error("error4".into())),
+            Error("error5".into()).with_label("b"),
+        ]
+        .into();
+
+        let flat_errors = tree.flatten_tree_sorted_by_label();
+
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec![], Error("error4".into())),
+                (vec!["c", "a"], Error("error2".into())),
+                (vec!["d", "a"], Error("error1".into())),
+                (vec!["b"], Error("error3".into())),
+                (vec!["b"], Error("error5".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn displayed_uses_thiserror_messages() {
+        #[derive(Debug, thiserror::Error)]
+        enum ThisError {
+            #[error("file {0} not found")]
+            NotFound(String),
+            #[error("permission denied")]
+            PermissionDenied,
+        }
+
+        let tree: ErrorTree<&'static str, ThisError> = vec![
+            ErrorTree::leaf(ThisError::NotFound("a.txt".into())).with_label("read"),
+            ErrorTree::leaf(ThisError::PermissionDenied).with_label("write"),
+        ]
+        .into();
+        let tree = tree.with_label("sync");
+
+        assert_eq!(
+            tree.displayed().to_string(),
+            "sync > read: file a.txt not found\nsync > write: permission denied"
+        );
+    }
+
+    #[test]
+    fn cloned_errors_clones_every_leaf() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            group.with_label("parent_label"),
+            ErrorTree::leaf(Error("error3".into())),
+        ]
+        .into();
+
+        assert_eq!(
+            tree.cloned_errors(),
+            vec![
+                Error("error1".into()),
+                Error("error2".into()),
+                Error("error3".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn label_leaves_by_labels_errors_by_category() {
+        let tree: ErrorTree<String, Error> = vec![
+            Error("io: disk full".into()).with_label("save".to_string()),
+            ErrorTree::leaf(Error("parse: bad token".into())),
+        ]
+        .into();
+
+        let tree =
+            tree.label_leaves_by(|Error(error)| error.split(':').next().unwrap().to_string());
+
+        let flat_errors = tree.flatten_tree();
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (
+                    vec!["io".to_string(), "save".to_string()],
+                    Error("io: disk full".into())
+                ),
+                (vec!["parse".to_string()], Error("parse: bad token".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn cap_height_collapses_deep_labels() {
+        let group: ErrorTree<String, Error> = vec![
+            Error("error1".into()).with_label("d".to_string()),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let tree: ErrorTree<String, Error> = vec![
+            group
+                .with_label("c".to_string())
+                .with_label("b".to_string())
+                .with_label("a".to_string()),
+            Error("error3".into()).with_label("e".to_string()),
+        ]
+        .into();
+
+        let flat_errors = tree.cap_height(1, |labels| labels.join("/")).flatten_tree();
+
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (
+                    vec!["b/c/d".to_string(), "a".to_string()],
+                    Error("error1".into())
+                ),
+                (
+                    vec!["b/c".to_string(), "a".to_string()],
+                    Error("error2".into())
+                ),
+                (vec!["e".to_string()], Error("error3".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn transform_removes_empty_vecs() {
+        let empty: ErrorTree<&'static str, Error> = vec![ErrorTree::Vec(vec![])].into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![]),
+            empty,
+            Error("error".into()).with_label("label"),
+        ]
+        .into();
+
+        let tree = tree.transform(|tree| match tree.into_node() {
+            Node::Vec(trees) => ErrorTree::Vec(
+                trees
+                    .into_iter()
+                    .filter(|tree| !matches!(tree, ErrorTree::Vec(trees) if trees.is_empty()))
+                    .collect_vec(),
+            ),
+            node => node.into(),
+        });
+
+        assert!(
+            matches!(
+                &tree,
+                ErrorTree::Vec(trees)
+                if matches!(&trees[..], [ErrorTree::Edge("label", _)])
+            ),
+            "unexpected: {:#?}",
+            tree
+        );
+    }
+
+    #[test]
+    fn from_option_labels_present_errors() {
+        let checks = vec![
+            ErrorTree::from_option("check1", Some(Error("error1".into()))),
+            ErrorTree::from_option("check2", None),
+        ];
+
+        assert!(
+            matches!(
+                &checks[..],
+                [
+                    Some(ErrorTree::Edge("check1", tree)),
+                    None,
+                ]
+                if matches!(&**tree, ErrorTree::Leaf(Error(error)) if error == "error1")
+            ),
+            "unexpected: {:#?}",
+            checks
+        );
+    }
+
+    #[test]
+    fn shallowest_error_prefers_the_first_shallowest() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into())
+                .with_label("label1")
+                .with_label("label2"),
+            Error("error2".into()).with_label("label3"),
+            Error("error3".into()).with_label("label4"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert_eq!(
+            tree.shallowest_error(),
+            Some((vec![&"parent_label", &"label3"], &Error("error2".into())))
+        );
+        assert_eq!(
+            ErrorTree::<&str, Error>::Vec(vec![]).shallowest_error(),
+            None
+        );
+    }
+
+    #[test]
+    fn triage_splits_mixed_trees() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("fatal1".into()).with_label("label1"),
+            Error("recoverable1".into()).with_label("label2"),
+            Error("fatal2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let (fatal, recoverable) = tree.triage(|Error(error)| error.starts_with("fatal"));
+
+        assert_eq!(
+            fatal
+                .unwrap()
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["label1", "parent_label"], Error("fatal1".into())),
+                (vec!["label2", "parent_label"], Error("fatal2".into())),
+            ]
+        );
+        assert_eq!(
+            recoverable
+                .unwrap()
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![(vec!["label2", "parent_label"], Error("recoverable1".into()))]
+        );
+    }
+
+    #[test]
+    fn triage_handles_all_fatal_trees() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("fatal1".into()).with_label("label1"),
+            Error("fatal2".into()).with_label("label2"),
+        ]
+        .into();
+
+        let (fatal, recoverable) = tree.triage(|Error(error)| error.starts_with("fatal"));
+
+        assert_eq!(fatal.unwrap().cloned_errors().len(), 2);
+        assert!(recoverable.is_none());
+    }
+
+    #[test]
+    fn triage_handles_all_recoverable_trees() {
+        let tree: ErrorTree<&'static str, Error> =
+            vec![Error("recoverable".into()).with_label("label")].into();
+
+        let (fatal, recoverable) = tree.triage(|Error(error)| error.starts_with("fatal"));
+
+        assert!(fatal.is_none());
+        assert_eq!(recoverable.unwrap().cloned_errors().len(), 1);
+    }
+
+    #[test]
+    fn with_shared_context_attaches_context_to_each_error() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+
+        let tree = tree.with_shared_context(Error("context".into()));
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["label1"], Error("error1".into())),
+                (vec!["label1"], Error("context".into())),
+                (vec![], Error("error2".into())),
+                (vec![], Error("context".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn structural_digest_is_equal_for_equal_trees() {
+        let tree = |error2: &str| -> ErrorTree<&'static str, Error> {
+            let tree: ErrorTree<_, _> = vec![
+                Error("error1".into()).with_label("label1"),
+                Error(error2.into()).with_label("label2"),
+            ]
+            .into();
+            tree.with_label("parent_label")
+        };
+
+        assert_eq!(
+            tree("error2").structural_digest(),
+            tree("error2").structural_digest()
+        );
+        assert_ne!(
+            tree("error2").structural_digest(),
+            tree("error3").structural_digest()
+        );
+    }
+
+    #[test]
+    fn fnv1a_hasher_writes_sizes_as_64_bits() {
+        let mut hasher1 = Fnv1aHasher::default();
+        hasher1.write_usize(42);
+        let mut hasher2 = Fnv1aHasher::default();
+        hasher2.write(&42u64.to_le_bytes());
+
+        assert_eq!(hasher1.finish(), hasher2.finish());
+    }
+
+    #[test]
+    fn structural_digest_depends_on_sibling_order() {
+        let tree1: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let tree2: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error2".into())),
+            ErrorTree::leaf(Error("error1".into())),
+        ]
+        .into();
+
+        assert_ne!(tree1.structural_digest(), tree2.structural_digest());
+    }
+
+    #[test]
+    fn to_rows_joins_paths() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert_eq!(
+            tree.to_rows("/"),
+            vec![
+                ["parent_label/label1".to_string(), "error1".to_string()],
+                ["parent_label".to_string(), "error2".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn prefix_labels_namespaces_every_label() {
+        let tree: ErrorTree<String, Error> = vec![
+            Error("error1".into()).with_label("label1".to_string()),
+            Error("error2".into()).with_label("label2".to_string()),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label".to_string());
+
+        let tree = tree.prefix_labels(|label| format!("A::{}", label));
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| e.path)
+                .collect_vec(),
+            vec![
+                vec!["A::label1".to_string(), "A::parent_label".to_string()],
+                vec!["A::label2".to_string(), "A::parent_label".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn flatten_vecs_merges_nested_vecs() {
+        let nested: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error2".into())),
+            ErrorTree::Vec(vec![ErrorTree::leaf(Error("error3".into()))]),
+        ]
+        .into();
+        let labeled: ErrorTree<&'static str, Error> = vec![ErrorTree::Vec(vec![ErrorTree::leaf(
+            Error("error5".into()),
+        )])]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![ErrorTree::leaf(Error("error1".into()))]),
+            nested,
+            Error("error4".into()).with_label("label"),
+            labeled.with_label("parent_label"),
+        ]
+        .into();
+
+        let tree = tree.flatten_vecs();
+
+        assert!(
+            matches!(
+                &tree,
+                ErrorTree::Vec(trees)
+                if matches!(
+                    &trees[..],
+                    [
+                        ErrorTree::Leaf(_),
+                        ErrorTree::Leaf(_),
+                        ErrorTree::Leaf(_),
+                        ErrorTree::Edge("label", _),
+                        ErrorTree::Edge("parent_label", labeled),
+                    ]
+                    if matches!(&**labeled, ErrorTree::Vec(trees) if matches!(&trees[..], [ErrorTree::Leaf(_)]))
+                )
+            ),
+            "unexpected: {:#?}",
+            tree
+        );
+        assert_eq!(
+            tree.cloned_errors(),
+            vec![
+                Error("error1".into()),
+                Error("error2".into()),
+                Error("error3".into()),
+                Error("error4".into()),
+                Error("error5".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn try_tree_labels_and_returns_early() {
+        fn run(fail: bool) -> Result<u32, ErrorTree<&'static str, Error>> {
+            try_tree!(if fail { faulty("error") } else { Ok(()) }, "step");
+            Ok(42)
+        }
+
+        assert!(matches!(run(false), Ok(42)));
+
+        let flat_errors = run(true).flatten_results().unwrap_err();
+        assert!(
+            matches!(
+                &flat_errors[..],
+                [FlatError { path, error: Error(error) }]
+                if path == &vec!["step"] && error == "error"
+            ),
+            "unexpected: {:#?}",
+            flat_errors
+        );
+    }
+
+    #[test]
+    fn distinct_path_count_counts_shared_paths_once() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label1"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            group.with_label("parent_label"),
+            Error("error3".into()).with_label("label1"),
+        ]
+        .into();
+
+        assert_eq!(tree.distinct_path_count(), 2);
+    }
+
+    #[test]
+    fn to_nested_map_merges_siblings_with_the_same_label() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            group.with_label("parent_label"),
+            Error("error3".into())
+                .with_label("label1")
+                .with_label("parent_label"),
+        ]
+        .into();
+
+        let nested = tree.to_nested_map();
+
+        assert!(nested.errors.is_empty());
+        assert_eq!(nested.branches.len(), 1);
+        let parent = &nested.branches["parent_label"];
+        assert!(parent.errors.is_empty());
+        assert_eq!(
+            parent.branches["label1"].errors,
+            vec![Error("error1".into()), Error("error3".into())]
+        );
+        assert_eq!(
+            parent.branches["label2"].errors,
+            vec![Error("error2".into())]
+        );
+    }
+
+    #[test]
+    fn collect_positional_keeps_alignment() {
+        let results = vec![Ok(1), faulty("error").map(|_| 2), Ok(3)];
+
+        let (successes, errors) = collect_positional(results);
+
+        assert_eq!(successes, vec![Some(1), None, Some(3)]);
+        assert_eq!(
+            errors
+                .unwrap()
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![(vec![1], Error("error".into()))]
+        );
+    }
+
+    #[test]
+    fn collect_positional_without_errors() {
+        let results: Vec<Result<u32, Error>> = vec![Ok(1), Ok(2)];
+
+        let (successes, errors) = collect_positional(results);
+
+        assert_eq!(successes, vec![Some(1), Some(2)]);
+        assert!(errors.is_none());
+    }
+
+    #[test]
+    fn string_tree_builds_string_errors() {
+        let tree = string_tree(
+            "parent_label",
+            vec![string_leaf("error1"), string_leaf("error2")],
+        );
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["parent_label".to_string()], "error1".to_string()),
+                (vec!["parent_label".to_string()], "error2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn most_common_error_counts_repeated_errors() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("rare".into()).with_label("label1"),
+            Error("common".into()).with_label("label2"),
+            Error("common".into()).with_label("label3"),
+            ErrorTree::leaf(Error("common".into())),
+        ]
+        .into();
+
+        assert_eq!(tree.most_common_error(), Some((&Error("common".into()), 3)));
+    }
+
+    #[test]
+    fn most_common_error_prefers_the_first_on_ties() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+
+        assert_eq!(tree.most_common_error(), Some((&Error("error1".into()), 1)));
+        assert_eq!(
+            ErrorTree::<&str, Error>::Vec(vec![]).most_common_error(),
+            None
+        );
+    }
+
+    #[test]
+    fn write_tree_writes_the_same_as_render_tree() {
+        let group: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, &'static str> =
+            vec![group.with_label("parent_label"), ErrorTree::leaf("error3")].into();
+
+        let mut rendered = String::new();
+        tree.write_tree(&mut rendered).unwrap();
+
+        assert_eq!(rendered, tree.render_tree());
+        assert_eq!(
+            rendered,
+            "parent_label\n  label1\n    error1\n  error2\nerror3\n"
+        );
+    }
+
+    #[test]
+    fn into_single_or_tree_returns_a_single_error() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![]),
+            Error("error".into()).with_label("label"),
+        ]
+        .into();
+
+        assert_eq!(tree.into_single_or_tree().unwrap(), Error("error".into()));
+    }
+
+    #[test]
+    fn into_single_or_tree_returns_trees_with_many_errors() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+
+        let tree = tree.into_single_or_tree().unwrap_err();
+
+        assert_eq!(tree.cloned_errors().len(), 2);
+        assert!(ErrorTree::<&str, Error>::Vec(vec![])
+            .into_single_or_tree()
+            .is_err());
+    }
+
+    #[test]
+    fn into_single_or_tree_returns_the_tree_unchanged() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![]),
+            ErrorTree::Vec(vec![
+                Error("error1".into()).with_label("label1"),
+                Error("error2".into()).with_label("label2"),
+            ])
+            .with_label("parent_label"),
+        ]
+        .into();
+
+        let tree = tree.into_single_or_tree().unwrap_err();
+
+        assert!(matches!(&tree, ErrorTree::Vec(trees) if trees.len() == 2));
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["label1", "parent_label"], Error("error1".into())),
+                (vec!["label2", "parent_label"], Error("error2".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn keyed_labels_display_names_and_compare_keys() {
+        let tree: ErrorTree<KeyedLabel, &'static str> = vec![
+            ErrorTree::leaf("erro1").with_label(KeyedLabel::new("name", "Nome")),
+            ErrorTree::leaf("erro2").with_label(KeyedLabel::new("name", "Nome completo")),
+        ]
+        .into();
+
+        assert_eq!(
+            tree.render_tree(),
+            "Nome\n  erro1\nNome completo\n  erro2\n"
+        );
+        assert_eq!(tree.distinct_path_count(), 1);
+
+        let flat_errors = tree.flatten_tree();
+        assert_eq!(flat_errors[0].path, vec![KeyedLabel::new("name", "Name")]);
+    }
+
+    #[test]
+    fn path_stats_summarizes_depths() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into())
+                .with_label("label2")
+                .with_label("label3"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            group.with_label("parent_label"),
+            ErrorTree::leaf(Error("error3".into())),
+        ]
+        .into();
+
+        assert_eq!(
+            tree.path_stats(),
+            PathStats {
+                min: 0,
+                max: 3,
+                mean: 5.0 / 3.0,
+                count: 3,
+            }
+        );
+        assert_eq!(
+            ErrorTree::<&str, Error>::Vec(vec![]).path_stats(),
+            PathStats {
+                min: 0,
+                max: 0,
+                mean: 0.0,
+                count: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn first_per_path_keeps_one_error_per_path() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+            Error("error3".into()).with_label("label1"),
+            Error("error4".into()).with_label("label1"),
+        ]
+        .into();
+
+        let flat_errors = tree.first_per_path();
+
+        assert_eq!(
+            flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["label1"], Error("error1".into())),
+                (vec!["label2"], Error("error2".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn try_vec_rejects_empty_trees() {
+        let tree = ErrorTree::<&'static str, Error>::try_vec(vec![ErrorTree::Vec(vec![])]);
+
+        assert_eq!(tree.unwrap_err(), ErrorTreeError::EmptyTree);
+    }
+
+    #[test]
+    fn try_vec_rejects_labels_without_errors() {
+        let tree = ErrorTree::<&'static str, Error>::try_vec(vec![
+            Error("error".into()).with_label("label1"),
+            ErrorTree::Vec(vec![]).with_label("label2"),
+        ]);
+
+        assert_eq!(tree.unwrap_err(), ErrorTreeError::InvalidStructure);
+    }
+
+    #[test]
+    fn try_vec_accepts_valid_trees() {
+        let tree = ErrorTree::<&'static str, Error>::try_vec(vec![
+            Error("error".into()).with_label("label"),
+            ErrorTree::Vec(vec![]),
+        ]);
+
+        assert_eq!(tree.unwrap().cloned_errors(), vec![Error("error".into())]);
+    }
+
+    #[test]
+    fn label_error_once_does_not_repeat_the_outer_label() {
+        let result = faulty("error").label_error_once("x").label_error_once("x");
+
+        let flat_errors = result.unwrap_err().flatten_tree();
+        assert_eq!(flat_errors.len(), 1);
+        assert_eq!(flat_errors[0].path, vec!["x"]);
+    }
+
+    #[test]
+    fn label_error_once_adds_different_labels() {
+        let result = faulty("error").label_error_once("x").label_error_once("y");
+
+        let flat_errors = result.unwrap_err().flatten_tree();
+        assert_eq!(flat_errors[0].path, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn flatten_tree_fast_paths_match_the_general_traversal() {
+        let shapes = || -> Vec<ErrorTree<&'static str, Error>> {
+            vec![
+                ErrorTree::leaf(Error("error".into())),
+                Error("error".into()).with_label("label"),
+                Error("error".into())
+                    .with_label("label")
+                    .with_label("parent_label"),
+                ErrorTree::Vec(vec![]).with_label("label"),
+            ]
+        };
+
+        for (tree, expected) in shapes().into_iter().zip(shapes()) {
+            let general = FlatIter::new(expected)
+                .map(|e| (e.path, e.error))
+                .collect_vec();
+            let fast = tree
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec();
+            assert_eq!(fast, general);
+        }
+    }
+
+    #[test]
+    fn normalize_labels_lowercases_every_label() {
+        let tree: ErrorTree<String, Error> = vec![
+            Error("error1".into()).with_label("Label".to_string()),
+            Error("error2".into())
+                .with_label("LABEL".to_string())
+                .with_label("Parent".to_string()),
+        ]
+        .into();
+
+        let flat_errors = tree
+            .normalize_labels(|label| label.to_lowercase())
+            .flatten_tree();
+
+        assert_eq!(
+            flat_errors.into_iter().map(|e| e.path).collect_vec(),
+            vec![
+                vec!["label".to_string()],
+                vec!["label".to_string(), "parent".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn labeled_with_children_flattens_the_node_error_and_its_children() {
+        let tree = ErrorTree::labeled_with_children(
+            "config",
+            Error("config invalid".into()),
+            vec![
+                Error("missing".into()).with_label("port"),
+                Error("empty".into()).with_label("host"),
+            ],
+        );
+
+        let flat_errors = tree
+            .flatten_tree()
+            .into_iter()
+            .map(|e| (e.path, e.error))
+            .collect_vec();
+
+        assert_eq!(
+            flat_errors,
+            vec![
+                (vec!["config"], Error("config invalid".into())),
+                (vec!["port", "config"], Error("missing".into())),
+                (vec!["host", "config"], Error("empty".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn to_compact_string_joins_every_error_in_one_line() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("err1").with_label("b").with_label("a"),
+            ErrorTree::leaf("err2"),
+            ErrorTree::leaf("err3").with_label("c"),
+        ]
+        .into();
+
+        assert_eq!(
+            tree.to_compact_string(" | ", "="),
+            "[a>b=err1 | err2 | c=err3]"
+        );
+    }
+
+    #[test]
+    fn common_label_prefix_finds_the_shared_labels() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into())
+                .with_label("label1")
+                .with_label("parent_label"),
+            Error("error2".into())
+                .with_label("label2")
+                .with_label("parent_label"),
+        ]
+        .into();
+
+        assert_eq!(tree.common_label_prefix(), vec!["parent_label"]);
+    }
+
+    #[test]
+    fn common_label_prefix_is_empty_when_paths_diverge_at_the_root() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+
+        assert!(tree.common_label_prefix().is_empty());
+    }
+
+    #[test]
+    fn factor_common_prefix_keeps_paths_and_shrinks_the_tree() {
+        let tree = || -> ErrorTree<&'static str, Error> {
+            vec![
+                Error("error1".into())
+                    .with_label("label1")
+                    .with_label("b")
+                    .with_label("a"),
+                ErrorTree::Vec(vec![Error("error2".into())
+                    .with_label("label2")
+                    .with_label("b")])
+                .with_label("a"),
+            ]
+            .into()
+        };
+
+        let factored = tree().factor_common_prefix();
+
+        assert_eq!(
+            factored
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            tree()
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec()
+        );
+        assert!(tree().factor_common_prefix().nodes().len() < tree().nodes().len());
+    }
+
+    #[test]
+    fn detailed_flatten_fills_every_field() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+
+        let detailed = tree.with_label("parent_label").detailed_flatten();
+
+        assert!(
+            matches!(
+                &detailed[..],
+                [
+                    DetailedError {
+                        index: 0,
+                        depth: 2,
+                        path: path1,
+                        error: Error(error1),
+                    },
+                    DetailedError {
+                        index: 1,
+                        depth: 1,
+                        path: path2,
+                        error: Error(error2),
+                    },
+                ]
+                if path1 == &vec!["parent_label", "label1"]
+                && path2 == &vec!["parent_label"]
+                && error1 == "error1"
+                && error2 == "error2"
+            ),
+            "unexpected: {:#?}",
+            detailed
+        );
+    }
+
+    #[test]
+    fn intern_errors_deduplicates_and_resolves_errors() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+            Error("error1".into()).with_label("label3"),
+        ]
+        .into();
+
+        let (tree, errors) = tree.intern_errors();
+
+        assert_eq!(errors, vec![Error("error1".into()), Error("error2".into())]);
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, errors[e.error].clone()))
+                .collect_vec(),
+            vec![
+                (vec!["label1"], Error("error1".into())),
+                (vec!["label2"], Error("error2".into())),
+                (vec!["label3"], Error("error1".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn label_with_labels_errors_by_a_field_of_their_items() {
+        struct Item {
+            name: &'static str,
+        }
+
+        let results = vec![
+            (Item { name: "a" }, faulty("error1")),
+            (Item { name: "b" }, Ok(())),
+            (Item { name: "c" }, faulty("error2")),
+        ];
+
+        let tree: ErrorTree<&'static str, Error> = results
+            .into_iter()
+            .label_with(|item| item.name)
+            .collect_tree()
+            .unwrap_err();
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["a"], Error("error1".into())),
+                (vec!["c"], Error("error2".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_mut_changes_every_error_in_place() {
+        let mut tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            vec![Error("error2".into()), Error("error3".into())]
+                .into_iter()
+                .map(ErrorTree::leaf)
+                .collect_vec()
+                .into(),
+        ]
+        .into();
+
+        for Error(message) in tree.leaves_mut() {
+            message.push('!');
+        }
+
+        assert_eq!(
+            tree.cloned_errors(),
+            vec![
+                Error("error1!".into()),
+                Error("error2!".into()),
+                Error("error3!".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn into_context_chain_follows_the_first_error() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![]).with_label("empty"),
+            ErrorTree::Vec(vec![
+                Error("error1".into()).with_label("label1"),
+                Error("error2".into()).with_label("label2"),
+            ]),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let (labels, error) = tree.into_context_chain().unwrap();
+
+        assert_eq!(labels, vec!["parent_label", "label1"]);
+        assert_eq!(error, Error("error1".into()));
+    }
+
+    #[test]
+    fn into_context_chain_is_none_without_errors() {
+        let tree: ErrorTree<&'static str, Error> = ErrorTree::Vec(vec![]).with_label("label");
+
+        assert!(tree.into_context_chain().is_none());
+    }
+
+    #[test]
+    fn eq_ignoring_labels_compares_shape_and_errors() {
+        let tree = |label1, label2| -> ErrorTree<&'static str, Error> {
+            vec![
+                Error("error1".into()).with_label(label1),
+                Error("error2".into()).with_label(label2),
+            ]
+            .into()
+        };
+
+        assert!(tree("a", "b").eq_ignoring_labels(&tree("c", "d")));
+        assert!(!tree("a", "b").eq_ignoring_labels(&tree("a", "b").with_label("e")));
+        assert!(!tree("a", "b").eq_ignoring_labels(&ErrorTree::Vec(vec![
+            Error("error1".into()).with_label("a"),
+            Error("error3".into()).with_label("b"),
+        ])));
+    }
+
+    #[test]
+    fn combine_sibling_leaves_combines_only_adjacent_leaves() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())),
+            ErrorTree::leaf(Error("error2".into())),
+            Error("error3".into()).with_label("label"),
+            ErrorTree::leaf(Error("error4".into())),
+        ]
+        .into();
+
+        let tree = tree.combine_sibling_leaves(|Error(error1), Error(error2)| {
+            Error(format!("{} and {}", error1, error2))
+        });
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec![], Error("error1 and error2".into())),
+                (vec!["label"], Error("error3".into())),
+                (vec![], Error("error4".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn shares_label_with_detects_overlapping_labels() {
+        let tree1: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("name"),
+            Error("error2".into())
+                .with_label("email")
+                .with_label("user"),
+        ]
+        .into();
+        let tree2: ErrorTree<&'static str, Error> = Error("error3".into()).with_label("user");
+        let tree3: ErrorTree<&'static str, Error> = Error("error4".into()).with_label("address");
+
+        assert!(tree1.shares_label_with(&tree2));
+        assert!(tree2.shares_label_with(&tree1));
+        assert!(!tree1.shares_label_with(&tree3));
+    }
+
+    #[test]
+    fn render_tree_with_uses_the_custom_indent() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert_eq!(
+            tree.render_tree_with("\t"),
+            "parent_label\n\tlabel1\n\t\terror1\n\terror2\n"
+        );
+        assert_eq!(tree.render_tree_with("  "), tree.render_tree());
+    }
 
-        let tree: ErrorTree<&'static str, Error> = errors.into();
+    #[test]
+    fn expect_path_returns_the_error_of_a_matching_path() {
+        let tree: ErrorTree<&'static str, Error> = Error("error".into())
+            .with_label("label")
+            .with_label("parent_label");
+
+        assert_eq!(
+            tree.expect_path(&["parent_label", "label"]).ok(),
+            Some(Error("error".into()))
+        );
+    }
+
+    #[test]
+    fn expect_path_returns_the_tree_otherwise() {
+        let tree: ErrorTree<&'static str, Error> = Error("error".into())
+            .with_label("label")
+            .with_label("parent_label");
+        let tree = tree.expect_path(&["label", "parent_label"]).unwrap_err();
+        assert_eq!(tree.count_leaves(), 1);
+
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label"),
+            Error("error2".into()).with_label("label"),
+        ]
+        .into();
+        let tree = tree.expect_path(&["label"]).unwrap_err();
+        assert_eq!(tree.count_leaves(), 2);
+    }
+
+    #[test]
+    fn flatten_paged_keeps_the_remainder_in_the_last_page() {
+        let tree: ErrorTree<&'static str, Error> = (1..=5)
+            .map(|i| Error(format!("error{}", i)).with_label("label"))
+            .collect_vec()
+            .into();
+
+        let pages = tree.flatten_paged(2);
+
+        assert_eq!(pages.iter().map(Vec::len).collect_vec(), vec![2, 2, 1]);
+        assert_eq!(pages[2][0].error, Error("error5".into()));
+    }
+
+    #[test]
+    fn flatten_paged_accepts_huge_page_sizes() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label"),
+            Error("error2".into()).with_label("label"),
+        ]
+        .into();
+
+        let pages = tree.flatten_paged(usize::MAX);
+
+        assert_eq!(pages.iter().map(Vec::len).collect_vec(), vec![2]);
+    }
+
+    #[test]
+    fn flatten_paged_has_no_pages_without_errors() {
+        let tree: ErrorTree<&'static str, Error> = ErrorTree::Vec(vec![]);
+
+        assert!(tree.flatten_paged(2).is_empty());
+    }
+
+    #[test]
+    fn collapse_paths_joins_the_labels_of_each_error() {
+        let tree = || -> ErrorTree<&'static str, &'static str> {
+            vec![
+                ErrorTree::leaf("error1").with_label("b").with_label("a"),
+                ErrorTree::leaf("error2"),
+                ErrorTree::Vec(vec![ErrorTree::leaf("error3").with_label("d")]),
+            ]
+            .into()
+        };
+
+        let collapsed = tree().collapse_paths("/").flatten_tree();
+        let original = tree().flatten_tree();
+
+        assert_eq!(collapsed.len(), original.len());
+        for (collapsed, original) in collapsed.into_iter().zip(original) {
+            let joined = original.path.iter().rev().join("/");
+            let expected = if joined.is_empty() {
+                vec![]
+            } else {
+                vec![joined]
+            };
+            assert_eq!(collapsed.path, expected);
+            assert_eq!(collapsed.error, original.error);
+        }
+    }
+
+    #[test]
+    fn depth_histogram_counts_errors_per_depth() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())),
+            Error("error2".into()).with_label("label1"),
+            Error("error3".into())
+                .with_label("label2")
+                .with_label("label3"),
+            ErrorTree::leaf(Error("error4".into())),
+        ]
+        .into();
+
+        assert_eq!(tree.depth_histogram(), vec![2, 1, 1]);
+        assert!(ErrorTree::<&'static str, Error>::Vec(vec![])
+            .depth_histogram()
+            .is_empty());
+    }
+
+    #[test]
+    fn label_nonempty_children_skips_children_without_errors() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())),
+            ErrorTree::Vec(vec![]),
+            Error("error2".into()).with_label("label"),
+            ErrorTree::Vec(vec![ErrorTree::Vec(vec![])]).with_label("empty"),
+        ]
+        .into();
+
+        let tree = tree.label_nonempty_children("item");
+
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["item"], Error("error1".into())),
+                (vec!["label", "item"], Error("error2".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn err_if_any_fails_only_with_errors() {
+        let empty: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![]),
+            ErrorTree::Vec(vec![]).with_label("label"),
+        ]
+        .into();
+        assert!(empty.err_if_any().is_ok());
+
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![]),
+            Error("error".into()).with_label("label"),
+        ]
+        .into();
+        let tree = tree.err_if_any().unwrap_err();
+        assert_eq!(tree.cloned_errors(), vec![Error("error".into())]);
+    }
+
+    #[test]
+    fn for_each_leaf_passes_the_root_first_path_of_each_error() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+            Error("error3".into())
+                .with_label("label2")
+                .with_label("label3"),
+        ]
+        .into();
         let tree = tree.with_label("parent_label");
 
-        let flat_errors = tree.flatten_tree();
+        let mut visited = Vec::new();
+        tree.for_each_leaf(|path, error| visited.push((path.to_vec(), error.clone())));
 
-        assert!(
-            matches!(
-                &flat_errors[..],
-                [
-                    FlatError {
-                        path: path1,
-                        error: Error(error1),
-                    },
-                    FlatError {
-                        path: path2,
-                        error: Error(error2),
-                    },
-                ]
-                if path1 == &vec!["label1", "parent_label"]
-                && path2 == &vec!["label2", "parent_label"]
-                && error1 == "error1"
-                && error2 == "error2"
-            ),
-            "unexpected: {:#?}",
-            flat_errors
+        assert_eq!(
+            visited,
+            vec![
+                (vec!["parent_label", "label1"], Error("error1".into())),
+                (vec!["parent_label"], Error("error2".into())),
+                (
+                    vec!["parent_label", "label3", "label2"],
+                    Error("error3".into())
+                ),
+            ]
         );
     }
 
     #[test]
-    fn can_call_into_result_from_vec_of_results() {
-        let result_1 = faulty("error1").map_err(|e| e.with_label("label1"));
-        let result_2 = faulty("error2").map_err(|e| e.with_label("label2"));
+    fn flatten_each_matches_flatten_tree() {
+        let tree = || -> ErrorTree<String, Error> {
+            vec![
+                Error("error1".into()).with_label("label1".to_string()),
+                ErrorTree::leaf(Error("error2".into())),
+                Error("error3".into())
+                    .with_label("label2".to_string())
+                    .with_label("label3".to_string()),
+            ]
+            .into()
+        };
 
-        let result: Result<Vec<()>, ErrorTree<_, _>> = vec![result_1, result_2]
-            .into_iter()
-            .partition_result()
-            .into_result();
+        let mut visited = Vec::new();
+        tree()
+            .with_label("parent_label".to_string())
+            .flatten_each(|path, error| {
+                visited.push((path.iter().rev().cloned().collect_vec(), error))
+            });
 
-        let flat_result = result.map_err(|e| e.flatten_tree());
+        assert_eq!(
+            visited,
+            tree()
+                .with_label("parent_label".to_string())
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec()
+        );
+    }
 
-        let flat_errors = flat_result.unwrap_err();
+    #[test]
+    fn redact_replaces_only_the_selected_errors() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("token abc123 expired".into()).with_label("auth"),
+            Error("not found".into()).with_label("user"),
+        ]
+        .into();
 
-        assert!(
-            matches!(
-                &flat_errors[..],
-                [
-                    FlatError {
-                        path: path1,
-                        error: Error(error1),
-                    },
-                    FlatError {
-                        path: path2,
-                        error: Error(error2),
-                    },
-                ]
-                if path1 == &vec!["label1"]
-                && path2 == &vec!["label2"]
-                && error1 == "error1"
-                && error2 == "error2"
-            ),
-            "unexpected: {:#?}",
+        let tree = tree.redact(|Error(message)| {
+            message
+                .starts_with("token")
+                .then(|| Error("token expired".into()))
+        });
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["auth"], Error("token expired".into())),
+                (vec!["user"], Error("not found".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn to_csv_quotes_fields_with_the_separator() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("expected a, b or c").with_label("letter"),
+            ErrorTree::leaf("too long").with_label("name"),
+        ]
+        .into();
+        let tree = tree.with_label("form");
+
+        assert_eq!(
+            tree.to_csv(","),
+            "path,error\nform > letter,\"expected a, b or c\"\nform > name,too long\n"
+        );
+        assert_eq!(
+            tree.to_csv(";"),
+            "path;error\nform > letter;expected a, b or c\nform > name;too long\n"
+        );
+    }
+
+    #[test]
+    fn estimated_render_len_is_a_lower_bound() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+            ErrorTree::leaf("error3")
+                .with_label("label2")
+                .with_label("label3"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert!(tree.estimated_render_len() <= tree.render_tree().len());
+        assert_eq!(
+            ErrorTree::<&str, &str>::leaf("")
+                .with_label("")
+                .estimated_render_len(),
+            ErrorTree::<&str, &str>::leaf("")
+                .with_label("")
+                .render_tree()
+                .len()
+        );
+    }
+
+    #[test]
+    fn into_primary_with_notes_keeps_the_other_errors_as_notes() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+            ErrorTree::leaf(Error("error3".into())),
+        ]
+        .into();
+
+        let (primary, notes) = tree.into_primary_with_notes().unwrap();
+
+        assert_eq!(primary, Error("error1".into()));
+        assert_eq!(
+            notes.into_iter().map(|e| (e.path, e.error)).collect_vec(),
+            vec![
+                (vec!["label2"], Error("error2".into())),
+                (vec![], Error("error3".into())),
+            ]
+        );
+        assert!(ErrorTree::<&'static str, Error>::Vec(vec![])
+            .into_primary_with_notes()
+            .is_none());
+    }
+
+    #[test]
+    fn assign_error_ids_numbers_errors_in_flatten_order() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            group.with_label("parent_label"),
+            ErrorTree::leaf(Error("error3".into())),
+        ]
+        .into();
+
+        let flat_errors = tree.assign_error_ids().flatten_tree();
+
+        assert_eq!(
             flat_errors
+                .into_iter()
+                .map(|e| (e.error.0, e.path, e.error.1))
+                .collect_vec(),
+            vec![
+                (0, vec!["label1", "parent_label"], Error("error1".into())),
+                (1, vec!["label2", "parent_label"], Error("error2".into())),
+                (2, vec![], Error("error3".into())),
+            ]
         );
     }
 
     #[test]
-    fn can_call_into_result_from_vec_of_errors() {
-        let error1 = Error("error1".into()).with_label("label1");
-        let error2 = Error("error2".into()).with_label("label2");
+    fn subtree_at_navigates_to_a_nested_leaf() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error3".into())),
+            group.with_label("parent_label"),
+        ]
+        .into();
 
-        let result: Result<_, ErrorTree<_, _>> = vec![error1, error2].into_result();
+        assert!(matches!(
+            tree.subtree_at(&[1, 1]),
+            Some(ErrorTree::Leaf(Error(error))) if error == "error2"
+        ));
+        assert!(matches!(tree.subtree_at(&[]), Some(ErrorTree::Vec(_))));
+        assert!(tree.subtree_at(&[0, 0]).is_none());
+        assert!(tree.subtree_at(&[1, 2]).is_none());
+    }
 
-        let flat_result = result.map_err(|e| e.flatten_tree());
+    #[test]
+    fn diff_splits_errors_between_the_trees() {
+        let tree1: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let tree2: ErrorTree<&'static str, Error> = vec![
+            Error("error2".into()).with_label("label2"),
+            Error("error1".into()).with_label("other_label"),
+        ]
+        .into();
 
-        let flat_errors = flat_result.unwrap_err();
+        let diff = tree1.diff(&tree2);
 
-        assert!(
-            matches!(
-                &flat_errors[..],
-                [
-                    FlatError {
-                        path: path1,
-                        error: Error(error1),
-                    },
-                    FlatError {
-                        path: path2,
-                        error: Error(error2),
-                    },
-                ]
-                if path1 == &vec!["label1"]
-                && path2 == &vec!["label2"]
-                && error1 == "error1"
-                && error2 == "error2"
-            ),
-            "unexpected: {:#?}",
+        let pairs = |errors: Vec<FlatError<&'static str, Error>>| {
+            errors.into_iter().map(|e| (e.path, e.error)).collect_vec()
+        };
+        assert_eq!(
+            pairs(diff.only_in_self),
+            vec![
+                (vec!["label1"], Error("error1".into())),
+                (vec!["label2"], Error("error2".into())),
+            ]
+        );
+        assert_eq!(
+            pairs(diff.only_in_other),
+            vec![(vec!["other_label"], Error("error1".into()))]
+        );
+        assert_eq!(
+            pairs(diff.in_both),
+            vec![(vec!["label2"], Error("error2".into()))]
+        );
+    }
+
+    #[test]
+    fn summarize_groups_keeps_one_error_per_group() {
+        let group1: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::Vec(vec![]),
+            Error("error1".into()).with_label("label1"),
+            Error("error2".into()).with_label("label2"),
+        ]
+        .into();
+        let group2: ErrorTree<&'static str, Error> = vec![
+            Error("error3".into()).with_label("label3"),
+            Error("error4".into()).with_label("label4"),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, Error> = vec![
+            group1.with_label("group1"),
+            ErrorTree::Vec(vec![]).with_label("empty"),
+            group2.with_label("group2"),
+        ]
+        .into();
+
+        let tree = tree.with_label("root").summarize_groups();
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["label1", "group1", "root"], Error("error1".into())),
+                (vec!["label3", "group2", "root"], Error("error3".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_shape_checks_structure_and_labels() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let shape = |label| {
+            Shape::Edge(
+                "parent_label",
+                Box::new(Shape::Vec(vec![
+                    Shape::Edge(label, Box::new(Shape::AnyLeaf)),
+                    Shape::AnyLeaf,
+                ])),
+            )
+        };
+
+        assert!(tree.matches_shape(&shape("label1")));
+        assert!(!tree.matches_shape(&shape("label2")));
+        assert!(!tree.matches_shape(&Shape::AnyLeaf));
+    }
+
+    #[test]
+    fn flatten_display_joins_the_labels_of_each_error() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+            Error("error3".into())
+                .with_label("label2")
+                .with_label("label3"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let flat_errors = tree.flatten_display(" / ");
+
+        assert_eq!(
             flat_errors
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                ("parent_label / label1".to_string(), Error("error1".into())),
+                ("parent_label".to_string(), Error("error2".into())),
+                (
+                    "parent_label / label3 / label2".to_string(),
+                    Error("error3".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn contextualize_prepends_the_path_to_each_error() {
+        let tree: ErrorTree<&'static str, String> = vec![
+            ErrorTree::leaf("error1".to_string()).with_label("label1"),
+            ErrorTree::leaf("error2".to_string()),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let tree = tree.contextualize(|path, error| format!("[{}] {}", path.join(" > "), error));
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (
+                    vec!["label1", "parent_label"],
+                    "[parent_label > label1] error1".to_string()
+                ),
+                (vec!["parent_label"], "[parent_label] error2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn likely_root_cause_is_the_first_deepest_error() {
+        let tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error1".into())),
+            Error("error2".into())
+                .with_label("label1")
+                .with_label("label2"),
+            Error("error3".into()).with_label("label3"),
+            Error("error4".into())
+                .with_label("label4")
+                .with_label("label5"),
+        ]
+        .into();
+
+        assert_eq!(
+            tree.likely_root_cause(),
+            Some((vec![&"label2", &"label1"], &Error("error2".into())))
+        );
+        assert_eq!(
+            ErrorTree::<&'static str, Error>::Vec(vec![]).likely_root_cause(),
+            None
+        );
+    }
+
+    #[test]
+    fn into_result_merging_keeps_the_errors_of_every_phase() {
+        let phase1: ((), Vec<ErrorTree<&'static str, Error>>) =
+            ((), vec![Error("error1".into()).with_label("phase1")]);
+        let existing = phase1.into_result_merging(None).err();
+
+        let phase2: ((), Vec<ErrorTree<&'static str, Error>>) = (
+            (),
+            vec![
+                Error("error2".into()).with_label("phase2"),
+                Error("error3".into()).with_label("phase2"),
+            ],
+        );
+        let tree = phase2.into_result_merging(existing).unwrap_err();
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec!["phase1"], Error("error1".into())),
+                (vec!["phase2"], Error("error2".into())),
+                (vec!["phase2"], Error("error3".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn into_result_merging_fails_with_only_existing_errors() {
+        let existing = Some(Error("error".into()).with_label("phase1"));
+        let phase2: (u32, Vec<ErrorTree<&'static str, Error>>) = (2, vec![]);
+
+        assert!(phase2.into_result_merging(existing).is_err());
+
+        let phase3: (u32, Vec<ErrorTree<&'static str, Error>>) = (3, vec![]);
+        assert_eq!(
+            phase3
+                .into_result_merging(Some(ErrorTree::Vec(vec![])))
+                .ok(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn to_ffi_errors_builds_owned_strings() {
+        let tree: ErrorTree<u32, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label(1),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        let tree = tree.with_label(0);
+
+        assert_eq!(
+            tree.to_ffi_errors(),
+            vec![
+                FfiError {
+                    path: "0 > 1".to_string(),
+                    message: "error1".to_string(),
+                },
+                FfiError {
+                    path: "0".to_string(),
+                    message: "error2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn all_leaves_labeled_finds_unlabeled_errors() {
+        let tree: ErrorTree<&'static str, Error> = ErrorTree::Vec(vec![
+            ErrorTree::leaf(Error("error1".into())),
+            Error("error2".into()).with_label("label"),
+        ]);
+        assert!(!tree.all_leaves_labeled());
+
+        let tree: ErrorTree<&'static str, Error> = ErrorTree::Vec(vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::Vec(vec![ErrorTree::leaf(Error("error2".into()))]).with_label("label2"),
+        ]);
+        assert!(tree.all_leaves_labeled());
+    }
+
+    #[test]
+    fn insert_label_at_wraps_the_selected_subtree() {
+        let group: ErrorTree<&'static str, Error> = vec![
+            Error("error1".into()).with_label("label1"),
+            ErrorTree::leaf(Error("error2".into())),
+        ]
+        .into();
+        let mut tree: ErrorTree<&'static str, Error> = vec![
+            ErrorTree::leaf(Error("error3".into())),
+            group.with_label("parent_label"),
+        ]
+        .into();
+
+        assert!(tree.insert_label_at(&[1, 1], "new_label"));
+        assert!(!tree.insert_label_at(&[0, 0], "ignored"));
+        assert!(!tree.insert_label_at(&[2], "ignored"));
+
+        assert_eq!(
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect_vec(),
+            vec![
+                (vec![], Error("error3".into())),
+                (vec!["label1", "parent_label"], Error("error1".into())),
+                (vec!["new_label", "parent_label"], Error("error2".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn into_string_error_renders_one_line_per_error() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        assert_eq!(
+            tree.into_string_error("/"),
+            "parent_label/label1: error1\nparent_label: error2"
         );
+        assert_eq!(
+            ErrorTree::<&'static str, &'static str>::leaf("error").into_string_error("/"),
+            "error"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "sep must not be empty")]
+    fn to_csv_rejects_an_empty_separator() {
+        let tree: ErrorTree<&'static str, &'static str> = ErrorTree::leaf("error");
+
+        tree.to_csv("");
     }
 
     // For the README
 
     // The error type
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
     struct Error(String);
 
     impl<L> From<Error> for ErrorTree<L, Error> {
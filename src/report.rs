@@ -0,0 +1,112 @@
+//! Reports of flattened error trees, ready to be presented to the user.
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use itertools::Itertools;
+
+use crate::{ErrorTree, FlatError};
+
+/// A flattened error tree, ready to be presented to the user.
+#[derive(Debug)]
+pub struct Report<L, E> {
+    errors: Vec<FlatError<L, E>>,
+    labels: Vec<L>,
+}
+
+impl<L, E> Report<L, E> {
+    /// The flattened errors, in the same order as `flatten_tree`.
+    pub fn errors(&self) -> &[FlatError<L, E>] {
+        &self.errors
+    }
+
+    /// The number of errors.
+    pub fn error_count(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// The distinct labels of the errors, in the order they first appear.
+    pub fn labels(&self) -> &[L] {
+        &self.labels
+    }
+
+    /// The number of distinct labels.
+    pub fn label_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns `true` if the report has no errors.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Consumes the report, returning the flattened errors.
+    pub fn into_errors(self) -> Vec<FlatError<L, E>> {
+        self.errors
+    }
+}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Clone + Eq + Hash,
+{
+    /**
+    Flattens the tree into a `Report`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2").with_label("label1"),
+    ]
+    .into();
+
+    let report = tree.report();
+
+    assert_eq!(report.error_count(), 2);
+    assert_eq!(report.labels(), &["label1"]);
+    ```
+    */
+    pub fn report(self) -> Report<L, E> {
+        let errors = self.flatten_tree();
+        let mut seen = HashSet::new();
+        let labels = errors
+            .iter()
+            .flat_map(|error| error.path.iter().rev())
+            .filter(|label| seen.insert(*label))
+            .cloned()
+            .collect_vec();
+        Report { errors, labels }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IntoErrorTree;
+
+    #[test]
+    fn report_counts_errors_and_labels() {
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("error2").with_label("label2"),
+            ErrorTree::leaf("error3").with_label("label1"),
+        ]
+        .into();
+        let tree = tree.with_label("parent_label");
+
+        let report = tree.report();
+
+        assert_eq!(report.error_count(), 3);
+        assert_eq!(report.label_count(), 3);
+        assert_eq!(report.labels(), &["parent_label", "label1", "label2"]);
+        assert!(!report.is_empty());
+        assert_eq!(
+            report
+                .into_errors()
+                .into_iter()
+                .map(|e| e.error)
+                .collect_vec(),
+            vec!["error1", "error2", "error3"]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! `syn` integration for procedural macros, available with the `proc-macro2` feature.
+use std::fmt::Display;
+
+use itertools::Itertools;
+use proc_macro2::Span;
+
+use crate::ErrorTree;
+
+/// A label with the `Span` of the code it refers to.
+#[derive(Debug, Clone)]
+pub struct SpannedLabel<L> {
+    /// Where the label points to in the macro input.
+    pub span: Span,
+    /// The label itself.
+    pub label: L,
+}
+
+impl<L: Display> Display for SpannedLabel<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl<E> ErrorTree<Span, E>
+where
+    E: Display,
+{
+    /**
+    Converts each error into a `syn::Error` at the span of its innermost label,
+    or at `Span::call_site()` if it has no labels.
+
+    ```rust
+    # use error_trees::*;
+    # use proc_macro2::Span;
+    let tree: ErrorTree<Span, &'static str> = ErrorTree::leaf("expected a struct").with_label(Span::call_site());
+
+    let errors = tree.into_syn_errors();
+
+    assert_eq!(errors[0].to_string(), "expected a struct");
+    ```
+    */
+    pub fn into_syn_errors(self) -> Vec<syn::Error> {
+        self.iter_paths()
+            .map(|(path, error)| {
+                let span = path.last().map_or_else(Span::call_site, |span| **span);
+                syn::Error::new(span, error)
+            })
+            .collect_vec()
+    }
+}
+
+impl<L, E> ErrorTree<SpannedLabel<L>, E>
+where
+    L: Display,
+    E: Display,
+{
+    /**
+    Converts each error into a `syn::Error` at the span of its innermost label,
+    or at `Span::call_site()` if it has no labels.
+
+    The message has the labels from the root joined by ` > `, followed by the error.
+
+    ```rust
+    # use error_trees::*;
+    # use proc_macro2::Span;
+    let tree: ErrorTree<SpannedLabel<&'static str>, &'static str> =
+        ErrorTree::leaf("expected a number").with_label(SpannedLabel {
+            span: Span::call_site(),
+            label: "port",
+        });
+
+    let errors = tree.into_syn_errors();
+
+    assert_eq!(errors[0].to_string(), "port: expected a number");
+    ```
+    */
+    pub fn into_syn_errors(self) -> Vec<syn::Error> {
+        self.iter_paths()
+            .map(|(path, error)| match path.last() {
+                Some(innermost) => syn::Error::new(
+                    innermost.span,
+                    format!("{}: {}", path.iter().join(" > "), error),
+                ),
+                None => syn::Error::new(Span::call_site(), error),
+            })
+            .collect_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::IntoErrorTree;
+
+    #[test]
+    fn into_syn_errors_reports_each_error_with_its_labels() {
+        let label = |label| SpannedLabel {
+            span: Span::call_site(),
+            label,
+        };
+        let tree: ErrorTree<SpannedLabel<&'static str>, &'static str> = vec![
+            ErrorTree::leaf("expected a number").with_label(label("port")),
+            ErrorTree::leaf("unknown attribute"),
+        ]
+        .into();
+        let tree = tree.with_label(label("config"));
+
+        let messages = tree
+            .into_syn_errors()
+            .into_iter()
+            .map(|error| error.to_string())
+            .collect_vec();
+
+        assert_eq!(
+            messages,
+            vec![
+                "config > port: expected a number",
+                "config: unknown attribute"
+            ]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! `proptest` integration, available with the `proptest` feature.
+use std::fmt::Debug;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::ErrorTree;
+
+/**
+Builds a `Strategy` that generates `ErrorTree`s from a strategy for the labels
+and a strategy for the errors.
+
+Shrinking removes children from `Vec` nodes and simplifies `Edge`s
+towards single leaves.
+
+```rust
+# use error_trees::*;
+# use proptest::prelude::*;
+# use proptest::test_runner::TestRunner;
+let mut runner = TestRunner::default();
+runner
+    .run(&error_tree_strategy(any::<u8>(), ".*"), |tree| {
+        tree.flatten_tree();
+        Ok(())
+    })
+    .unwrap();
+```
+*/
+pub fn error_tree_strategy<L, E>(
+    labels: impl Strategy<Value = L> + Clone + 'static,
+    errors: impl Strategy<Value = E> + 'static,
+) -> impl Strategy<Value = ErrorTree<L, E>>
+where
+    L: Debug + Clone + 'static,
+    E: Debug + 'static,
+{
+    errors
+        .prop_map(ErrorTree::Leaf)
+        .prop_recursive(4, 64, 8, move |inner| {
+            prop_oneof![
+                (labels.clone(), inner.clone())
+                    .prop_map(|(label, tree)| ErrorTree::Edge(label, Box::new(tree))),
+                vec(inner, 0..8).prop_map(ErrorTree::Vec),
+            ]
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn flatten_tree_preserves_leaf_count(tree in error_tree_strategy(0..4u8, any::<u8>())) {
+            let leaves = tree.count_leaves();
+            prop_assert_eq!(tree.flatten_tree().len(), leaves);
+        }
+    }
+}
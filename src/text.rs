@@ -0,0 +1,324 @@
+//! A textual format for error trees, written by `to_text` and read by `from_text`.
+use std::fmt::Display;
+
+use crate::ErrorTree;
+
+/// The error returned when parsing an `ErrorTree` fails.
+///
+/// The variants depend on the enabled features, so matches on it need a wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The input ended where a node was expected.
+    UnexpectedEnd,
+    /// The line (starting at 1) is not indented as expected.
+    Indentation { line: usize },
+    /// The line doesn't start with `+ `, `- ` or `*`.
+    InvalidLine { line: usize },
+    /// The line has an invalid escape sequence.
+    InvalidEscape { line: usize },
+    /// The JSON value doesn't have the shape of an error tree.
+    #[cfg(feature = "serde")]
+    InvalidJson,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseError::Indentation { line } => {
+                write!(f, "unexpected indentation at line {}", line)
+            }
+            ParseError::InvalidLine { line } => write!(f, "invalid line {}", line),
+            ParseError::InvalidEscape { line } => {
+                write!(f, "invalid escape sequence at line {}", line)
+            }
+            #[cfg(feature = "serde")]
+            ParseError::InvalidJson => write!(f, "invalid JSON error tree"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<L, E> ErrorTree<L, E>
+where
+    L: Display,
+    E: Display,
+{
+    /**
+    Renders the tree in a textual format, that can be parsed back with `ErrorTree::from_text`.
+
+    Each node is written in its own line, indented by two spaces per level:
+    - An `Edge` is written as `+ label`, followed by its subtree.
+    - A `Leaf` is written as `- error`.
+    - A `Vec` is written as `*`, followed by its subtrees.
+
+    Backslashes and line breaks in the labels and errors are escaped as `\\`, `\n` and `\r`.
+
+    ```rust
+    # use error_trees::*;
+    let tree: ErrorTree<&'static str, &'static str> = vec![
+        ErrorTree::leaf("error1").with_label("label1"),
+        ErrorTree::leaf("error2"),
+    ]
+    .into();
+
+    assert_eq!(tree.to_text(), "*\n  + label1\n    - error1\n  - error2\n");
+    ```
+    */
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, tree)) = stack.pop() {
+            text.push_str(&"  ".repeat(depth));
+            match tree {
+                ErrorTree::Leaf(error) => {
+                    text.push_str("- ");
+                    text.push_str(&escape(&error.to_string()));
+                }
+                ErrorTree::Edge(label, tree) => {
+                    text.push_str("+ ");
+                    text.push_str(&escape(&label.to_string()));
+                    stack.push((depth + 1, tree));
+                }
+                ErrorTree::Vec(trees) => {
+                    text.push('*');
+                    stack.extend(trees.iter().rev().map(|tree| (depth + 1, tree)));
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl ErrorTree<String, String> {
+    /**
+    Parses a tree in the format written by `to_text`.
+
+    Blank lines are ignored.
+
+    ```rust
+    # use error_trees::*;
+    let tree = ErrorTree::from_text("+ label\n  - error\n").unwrap();
+
+    let flat_errors = tree.flatten_tree();
+    assert_eq!(flat_errors[0].path, vec!["label".to_string()]);
+    assert_eq!(flat_errors[0].error, "error");
+    ```
+    */
+    pub fn from_text(s: &str) -> Result<ErrorTree<String, String>, ParseError> {
+        let mut parser = Parser {
+            lines: s
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index + 1, line))
+                .filter(|(_, line)| !line.trim().is_empty())
+                .collect(),
+            position: 0,
+        };
+        let tree = parser.parse()?;
+        match parser.lines.get(parser.position) {
+            Some((line, _)) => Err(ParseError::Indentation { line: *line }),
+            None => Ok(tree),
+        }
+    }
+}
+
+struct Parser<'a> {
+    /// The non blank lines, with their line numbers.
+    lines: Vec<(usize, &'a str)>,
+    position: usize,
+}
+
+/// A node whose subtrees are still being parsed.
+enum Frame {
+    /// An `Edge` waiting for its subtree.
+    Edge(String),
+    /// A `Vec` at the given depth, with the subtrees parsed so far.
+    Vec(Vec<ErrorTree<String, String>>, usize),
+}
+
+impl<'a> Parser<'a> {
+    /// Parses the next node, with its subtrees, at depth `0`.
+    ///
+    /// The unfinished nodes are kept in an explicit stack, so deep inputs can't overflow the call stack.
+    fn parse(&mut self) -> Result<ErrorTree<String, String>, ParseError> {
+        let mut stack = Vec::new();
+        let mut depth = 0;
+        loop {
+            let (line, text) = *self
+                .lines
+                .get(self.position)
+                .ok_or(ParseError::UnexpectedEnd)?;
+            if indentation(text) != 2 * depth {
+                return Err(ParseError::Indentation { line });
+            }
+            let content = &text[2 * depth..];
+            self.position += 1;
+
+            let mut tree = if content == "*" {
+                if self.next_is_nested(depth + 1) {
+                    stack.push(Frame::Vec(Vec::new(), depth));
+                    depth += 1;
+                    continue;
+                }
+                ErrorTree::Vec(Vec::new())
+            } else if let Some(label) = content.strip_prefix('+') {
+                let label = unescape(label.strip_prefix(' ').unwrap_or(label), line)?;
+                stack.push(Frame::Edge(label));
+                depth += 1;
+                continue;
+            } else if let Some(error) = content.strip_prefix('-') {
+                ErrorTree::Leaf(unescape(error.strip_prefix(' ').unwrap_or(error), line)?)
+            } else {
+                return Err(ParseError::InvalidLine { line });
+            };
+
+            // Attaches the finished tree to its parents, until one of them has more subtrees to parse.
+            loop {
+                match stack.pop() {
+                    None => return Ok(tree),
+                    Some(Frame::Edge(label)) => tree = ErrorTree::Edge(label, Box::new(tree)),
+                    Some(Frame::Vec(mut trees, vec_depth)) => {
+                        trees.push(tree);
+                        if self.next_is_nested(vec_depth + 1) {
+                            stack.push(Frame::Vec(trees, vec_depth));
+                            depth = vec_depth + 1;
+                            break;
+                        }
+                        tree = ErrorTree::Vec(trees);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks if the next line is indented at least at `depth`.
+    fn next_is_nested(&self, depth: usize) -> bool {
+        self.lines
+            .get(self.position)
+            .is_some_and(|(_, text)| indentation(text) >= 2 * depth)
+    }
+}
+
+/// Counts the spaces at the start of `text`.
+fn indentation(text: &str) -> usize {
+    text.len() - text.trim_start_matches(' ').len()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(s: &str, line: usize) -> Result<String, ParseError> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('\\') => unescaped.push('\\'),
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                _ => return Err(ParseError::InvalidEscape { line }),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IntoErrorTree;
+
+    #[test]
+    fn from_text_parses_to_text() {
+        let group: ErrorTree<&'static str, &'static str> = vec![
+            ErrorTree::leaf("error1").with_label("label1"),
+            ErrorTree::leaf("multi\nline \\ error"),
+            ErrorTree::Vec(vec![]),
+        ]
+        .into();
+        let tree: ErrorTree<&'static str, &'static str> = vec![
+            group.with_label("parent_label"),
+            ErrorTree::leaf("error3")
+                .with_label("label3")
+                .with_label("label2"),
+        ]
+        .into();
+
+        let text = tree.to_text();
+        let parsed = ErrorTree::from_text(&text).unwrap();
+
+        assert_eq!(parsed.to_text(), text);
+        assert_eq!(
+            parsed
+                .flatten_tree()
+                .into_iter()
+                .map(|e| (e.path, e.error))
+                .collect::<Vec<_>>(),
+            tree.flatten_tree()
+                .into_iter()
+                .map(|e| (
+                    e.path.into_iter().map(String::from).collect::<Vec<_>>(),
+                    e.error.to_string()
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn from_text_handles_deep_trees() {
+        let depth = 2_000;
+        let mut text = String::new();
+        for level in 0..depth {
+            text.push_str(&format!("{}+ {}\n", "  ".repeat(level), level));
+        }
+        text.push_str(&format!("{}- error\n", "  ".repeat(depth)));
+
+        // A small stack, that a parser recursing once per level would overflow.
+        let flat_errors = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || ErrorTree::from_text(&text).unwrap().flatten_tree())
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(flat_errors[0].path.len(), depth);
+        assert_eq!(flat_errors[0].path[0], (depth - 1).to_string());
+    }
+
+    #[test]
+    fn from_text_reports_invalid_input() {
+        assert_eq!(
+            ErrorTree::from_text("").unwrap_err(),
+            ParseError::UnexpectedEnd
+        );
+        assert_eq!(
+            ErrorTree::from_text("+ label\n").unwrap_err(),
+            ParseError::UnexpectedEnd
+        );
+        assert_eq!(
+            ErrorTree::from_text("+ label\n    - error\n").unwrap_err(),
+            ParseError::Indentation { line: 2 }
+        );
+        assert_eq!(
+            ErrorTree::from_text("- error1\n- error2\n").unwrap_err(),
+            ParseError::Indentation { line: 2 }
+        );
+        assert_eq!(
+            ErrorTree::from_text("*\n\n  error\n").unwrap_err(),
+            ParseError::InvalidLine { line: 3 }
+        );
+        assert_eq!(
+            ErrorTree::from_text("- error \\t\n").unwrap_err(),
+            ParseError::InvalidEscape { line: 1 }
+        );
+    }
+}